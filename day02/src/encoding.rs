@@ -0,0 +1,111 @@
+//! Compact binary encoding for large course files. Every instruction is a single LEB128 varint:
+//! the zigzag-encoded magnitude shifted left by two, with the action in the low two bits. Typical
+//! puzzle instructions (magnitudes below 16) therefore take one byte each.
+
+use std::{error, fmt};
+
+use crate::Instruction;
+
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    /// The input ends in the middle of a varint that starts at this byte offset.
+    Truncated(usize),
+    /// The varint starting at this byte offset does not fit an instruction.
+    Overflow(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Truncated(offset) => write!(f, "truncated instruction at byte {}", offset),
+            Self::Overflow(offset) => write!(f, "instruction too large at byte {}", offset),
+        }
+    }
+}
+
+impl error::Error for DecodeError {}
+
+pub fn encode(instructions: &[Instruction]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(instructions.len());
+    for instruction in instructions {
+        let (tag, n) = match *instruction {
+            Instruction::Forward(n) => (0, n),
+            Instruction::Backward(n) => (1, n),
+            Instruction::Up(n) => (2, n),
+            Instruction::Down(n) => (3, n),
+        };
+        let zigzag = ((n << 1) ^ (n >> 63)) as u64;
+        let mut value = (zigzag as u128) << 2 | tag;
+        while value >= 0x80 {
+            bytes.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        bytes.push(value as u8);
+    }
+    bytes
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Vec<Instruction>, DecodeError> {
+    let mut instructions = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let start = offset;
+        let mut value = 0u128;
+        let mut shift = 0;
+        loop {
+            let byte = *bytes.get(offset).ok_or(DecodeError::Truncated(start))?;
+            offset += 1;
+            value |= ((byte & 0x7f) as u128) << shift;
+            if value >> 66 != 0 {
+                return Err(DecodeError::Overflow(start));
+            }
+            if byte & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+            if shift > 63 + 2 {
+                return Err(DecodeError::Overflow(start));
+            }
+        }
+
+        let zigzag = (value >> 2) as u64;
+        let n = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+        instructions.push(match value & 0b11 {
+            0 => Instruction::Forward(n),
+            1 => Instruction::Backward(n),
+            2 => Instruction::Up(n),
+            _ => Instruction::Down(n),
+        });
+    }
+    Ok(instructions)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::{encoding::*, tests::instruction};
+
+    proptest! {
+        #[test]
+        fn test_encoding_round_trip(instructions in prop::collection::vec(instruction(), 0..64)) {
+            prop_assert_eq!(decode(&encode(&instructions)), Ok(instructions));
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        let instructions = [
+            Instruction::Forward(5),
+            Instruction::Down(-1),
+            Instruction::Up(100),
+        ];
+        assert_eq!(encode(&instructions), [0x28, 0x07, 0xa2, 0x06]);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(decode(&[0x28, 0x80]), Err(DecodeError::Truncated(1)));
+        assert_eq!(decode(&[0xff; 11]), Err(DecodeError::Overflow(0)));
+    }
+}
@@ -1,90 +1,471 @@
-use std::{error, fmt, str::FromStr};
+mod encoding;
+mod planner;
+mod trace;
 
-#[derive(Debug)]
+use std::{error, fmt, fs, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// Every variant carries the 1-based line number the problem was found on.
+#[derive(Debug, PartialEq)]
 enum ParseInstructionError {
-    ParseIntError(std::num::ParseIntError, String),
-    SplitError(String),
-    UnknownAction(String),
+    ParseIntError(usize, std::num::ParseIntError, String),
+    MissingValue(usize, String),
+    UnknownAction(usize, String),
+    UnexpectedEnd(usize),
+    ExpectedBrace(usize, String),
+    UnclosedRepeat(usize),
+    UnmatchedBrace(usize),
+    TrailingInput(usize, String),
+    /// A `repeat` block nested deeper than `MAX_DEPTH`.
+    TooDeep(usize),
+    /// The course would run more than `MAX_INSTRUCTIONS` instructions once repeats are counted.
+    TooLong(usize),
 }
 
 impl fmt::Display for ParseInstructionError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::ParseIntError(s, e) => write!(f, "{}: {:?}", e, s),
-            Self::SplitError(s) => write!(f, "failed to split string: {:?}", s),
-            Self::UnknownAction(s) => write!(f, "unknown action in string: {:?}", s),
+            Self::ParseIntError(line, e, s) => write!(f, "line {}: {}: {:?}", line, e, s),
+            Self::MissingValue(line, s) => write!(f, "line {}: missing value after {:?}", line, s),
+            Self::UnknownAction(line, s) => write!(f, "line {}: unknown action {:?}", line, s),
+            Self::UnexpectedEnd(line) => write!(f, "line {}: expected an instruction", line),
+            Self::ExpectedBrace(line, s) => {
+                write!(f, "line {}: expected '{{', found {:?}", line, s)
+            }
+            Self::UnclosedRepeat(line) => write!(f, "line {}: repeat block is never closed", line),
+            Self::UnmatchedBrace(line) => write!(f, "line {}: unmatched '}}'", line),
+            Self::TrailingInput(line, s) => write!(f, "line {}: unexpected {:?}", line, s),
+            Self::TooDeep(line) => write!(
+                f,
+                "line {}: repeat blocks nested more than {} deep",
+                line, MAX_DEPTH
+            ),
+            Self::TooLong(line) => write!(
+                f,
+                "line {}: course runs more than {} instructions",
+                line, MAX_INSTRUCTIONS
+            ),
         }
     }
 }
 
 impl error::Error for ParseInstructionError {}
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 enum Instruction {
-    Forward(u64),
-    Up(u64),
-    Down(u64),
+    Forward(i64),
+    Backward(i64),
+    Up(i64),
+    Down(i64),
+}
+
+/// Splits a course into `(line, token)` pairs. Comments run from `#` to the end of the line and
+/// braces are always tokens of their own, so `repeat 2 {forward 1}` needs no extra spaces.
+fn tokenize(input: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    for (s, line) in input.lines().zip(1..) {
+        let code = s.split('#').next().unwrap_or_default();
+        let mut start = None;
+        for (i, c) in code.char_indices() {
+            if c.is_whitespace() || c == '{' || c == '}' {
+                if let Some(j) = start.take() {
+                    tokens.push((line, &code[j..i]));
+                }
+                if c != '{' && c != '}' {
+                    continue;
+                }
+                tokens.push((line, &code[i..i + 1]));
+            } else if start.is_none() {
+                start = Some(i);
+            }
+        }
+        if let Some(j) = start {
+            tokens.push((line, &code[j..]));
+        }
+    }
+    tokens
+}
+
+/// Parses the magnitude following `action`, e.g. the `5` in `forward 5`.
+fn parse_value<'a, T: FromStr<Err = std::num::ParseIntError>>(
+    tokens: &mut impl Iterator<Item = (usize, &'a str)>,
+    line: usize,
+    action: &str,
+) -> Result<T, ParseInstructionError> {
+    let (line, value) = tokens
+        .next()
+        .filter(|&(_, value)| value != "{" && value != "}")
+        .ok_or_else(|| ParseInstructionError::MissingValue(line, action.to_owned()))?;
+    value
+        .parse()
+        .map_err(|e| ParseInstructionError::ParseIntError(line, e, value.to_owned()))
+}
+
+fn parse_action<'a>(
+    tokens: &mut impl Iterator<Item = (usize, &'a str)>,
+    line: usize,
+    action: &str,
+) -> Result<Instruction, ParseInstructionError> {
+    let instruction = match action {
+        "forward" => Instruction::Forward,
+        "backward" => Instruction::Backward,
+        "up" => Instruction::Up,
+        "down" => Instruction::Down,
+        _ => {
+            return Err(ParseInstructionError::UnknownAction(
+                line,
+                action.to_owned(),
+            ))
+        }
+    };
+    Ok(instruction(parse_value(tokens, line, action)?))
+}
+
+/// How deeply `repeat` blocks may nest.
+const MAX_DEPTH: usize = 64;
+
+/// How many instructions a course may run, counting every repetition.
+const MAX_INSTRUCTIONS: u64 = 1 << 32;
+
+/// A parsed statement, with `repeat` blocks kept as a tree rather than expanded.
+#[derive(Debug, Clone, PartialEq)]
+enum Statement {
+    Instruction(usize, Instruction),
+    Repeat(u64, Vec<Statement>),
+}
+
+/// A parsed course, and the number of instructions it runs.
+#[derive(Debug, PartialEq)]
+struct Course {
+    statements: Vec<Statement>,
+    len: u64,
+}
+
+impl Course {
+    /// Every instruction in running order with the line it was written on, expanding `repeat`
+    /// blocks as it goes.
+    fn instructions(&self) -> Instructions<'_> {
+        Instructions {
+            stack: vec![(&self.statements[..], 0, 1)],
+        }
+    }
+}
+
+/// Walks a course with an explicit stack of `(block, next statement, runs left)`, so neither
+/// deep nesting nor large repeat counts cost more than the block tree itself.
+struct Instructions<'a> {
+    stack: Vec<(&'a [Statement], usize, u64)>,
+}
+
+impl<'a> Iterator for Instructions<'a> {
+    type Item = (usize, &'a Instruction);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (block, next, runs) = self.stack.last_mut()?;
+            let Some(statement) = block.get(*next) else {
+                if *runs > 1 {
+                    *runs -= 1;
+                    *next = 0;
+                } else {
+                    self.stack.pop();
+                }
+                continue;
+            };
+            *next += 1;
+            match statement {
+                Statement::Instruction(line, instruction) => return Some((*line, instruction)),
+                Statement::Repeat(count, body) if *count > 0 && !body.is_empty() => {
+                    self.stack.push((body, 0, *count))
+                }
+                Statement::Repeat(..) => {}
+            }
+        }
+    }
+}
+
+/// Parses statements up to the closing brace of the enclosing `repeat` block (opened on
+/// `opened`, `depth` blocks deep), or to the end of input at the top level. Returns them with
+/// the number of instructions they run.
+fn parse_block<'a>(
+    tokens: &mut impl Iterator<Item = (usize, &'a str)>,
+    opened: Option<usize>,
+    depth: usize,
+) -> Result<(Vec<Statement>, u64), ParseInstructionError> {
+    let (mut statements, mut len) = (Vec::new(), 0u64);
+    loop {
+        let (line, runs) = match (tokens.next(), opened) {
+            (None, None) | (Some((_, "}")), Some(_)) => return Ok((statements, len)),
+            (None, Some(line)) => return Err(ParseInstructionError::UnclosedRepeat(line)),
+            (Some((line, "}")), None) => return Err(ParseInstructionError::UnmatchedBrace(line)),
+            (Some((line, "repeat")), _) => {
+                let count: u64 = parse_value(tokens, line, "repeat")?;
+                match tokens.next() {
+                    Some((_, "{")) => {}
+                    Some((line, s)) => {
+                        return Err(ParseInstructionError::ExpectedBrace(line, s.to_owned()))
+                    }
+                    None => return Err(ParseInstructionError::UnclosedRepeat(line)),
+                }
+                if depth == MAX_DEPTH {
+                    return Err(ParseInstructionError::TooDeep(line));
+                }
+                let (body, body_len) = parse_block(tokens, Some(line), depth + 1)?;
+                statements.push(Statement::Repeat(count, body));
+                (line, count.checked_mul(body_len))
+            }
+            (Some((line, action)), _) => {
+                let instruction = parse_action(tokens, line, action)?;
+                statements.push(Statement::Instruction(line, instruction));
+                (line, Some(1))
+            }
+        };
+        len = runs
+            .and_then(|runs| len.checked_add(runs))
+            .filter(|&len| len <= MAX_INSTRUCTIONS)
+            .ok_or(ParseInstructionError::TooLong(line))?;
+    }
+}
+
+/// Parses a whole course file. Each instruction keeps the line it was written on.
+fn parse_course(input: &str) -> Result<Course, ParseInstructionError> {
+    let (statements, len) = parse_block(&mut tokenize(input).into_iter(), None, 0)?;
+    Ok(Course { statements, len })
 }
 
 impl FromStr for Instruction {
     type Err = ParseInstructionError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (action, value) = s
-            .split_once(' ')
-            .ok_or_else(|| Self::Err::SplitError(s.to_owned()))?;
-        let value = value
-            .parse()
-            .map_err(|e| Self::Err::ParseIntError(e, s.to_owned()))?;
-        match action {
-            "forward" => Ok(Self::Forward(value)),
-            "up" => Ok(Self::Up(value)),
-            "down" => Ok(Self::Down(value)),
-            _ => Err(Self::Err::UnknownAction(action.to_owned())),
-        }
-    }
-}
-
-fn parse_instruction(s: &str) -> Instruction {
-    s.parse::<Instruction>().unwrap()
-}
-
-fn part1(input: &str) -> u64 {
-    let (x, y) = input
-        .lines()
-        .map(parse_instruction)
-        .fold((0, 0), |(x, y), instruction| match instruction {
-            Instruction::Forward(n) => (x + n, y),
-            Instruction::Up(n) => (x, y - n),
-            Instruction::Down(n) => (x, y + n),
-        });
-    x * y
-}
-
-fn part2(input: &str) -> u64 {
-    let (x, y, _) =
-        input
-            .lines()
-            .map(parse_instruction)
-            .fold((0, 0, 0), |(x, y, a), instruction| match instruction {
-                Instruction::Forward(n) => (x + n, y + a * n, a),
-                Instruction::Up(n) => (x, y, a - n),
-                Instruction::Down(n) => (x, y, a + n),
-            });
-    x * y
+        let mut tokens = tokenize(s).into_iter();
+        let (line, action) = tokens.next().ok_or(Self::Err::UnexpectedEnd(1))?;
+        let instruction = parse_action(&mut tokens, line, action)?;
+        match tokens.next() {
+            Some((line, s)) => Err(Self::Err::TrailingInput(line, s.to_owned())),
+            None => Ok(instruction),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Forward(n) => write!(f, "forward {}", n),
+            Self::Backward(n) => write!(f, "backward {}", n),
+            Self::Up(n) => write!(f, "up {}", n),
+            Self::Down(n) => write!(f, "down {}", n),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum CourseError {
+    Parse(ParseInstructionError),
+    /// An intermediate value (or the final product) does not fit in an `i64`. Carries the 1-based
+    /// line number of the offending instruction, if there is one.
+    Overflow(Option<usize>),
+    AboveSurface {
+        line: usize,
+        depth: i64,
+    },
+}
+
+impl fmt::Display for CourseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::Overflow(Some(line)) => write!(f, "arithmetic overflow on line {}", line),
+            Self::Overflow(None) => write!(f, "arithmetic overflow computing the final product"),
+            Self::AboveSurface { line, depth } => {
+                write!(f, "submarine surfaced to depth {} on line {}", depth, line)
+            }
+        }
+    }
+}
+
+impl error::Error for CourseError {}
+
+impl From<ParseInstructionError> for CourseError {
+    fn from(e: ParseInstructionError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+/// What to do when an instruction would take the submarine above the surface (negative depth).
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SurfacePolicy {
+    Clamp,
+    Error,
+}
+
+impl SurfacePolicy {
+    fn apply(self, depth: i64, line: usize) -> Result<i64, CourseError> {
+        match self {
+            _ if depth >= 0 => Ok(depth),
+            Self::Clamp => Ok(0),
+            Self::Error => Err(CourseError::AboveSurface { line, depth }),
+        }
+    }
+}
+
+/// How instructions move the submarine: part 1 changes depth directly, part 2 steers via aim.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Model {
+    Direct,
+    Aim,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Position {
+    horizontal: i64,
+    depth: i64,
+    aim: i64,
+}
+
+impl Position {
+    fn step(
+        self,
+        instruction: &Instruction,
+        line: usize,
+        model: Model,
+        policy: SurfacePolicy,
+    ) -> Result<Self, CourseError> {
+        let checked = |value: Option<i64>| value.ok_or(CourseError::Overflow(Some(line)));
+        // Backward and up are forward and down with the sign flipped.
+        let (n, horizontal) = match *instruction {
+            Instruction::Forward(n) => (n, true),
+            Instruction::Backward(n) => (checked(n.checked_neg())?, true),
+            Instruction::Down(n) => (n, false),
+            Instruction::Up(n) => (checked(n.checked_neg())?, false),
+        };
+
+        Ok(match (horizontal, model) {
+            (true, Model::Direct) => Self {
+                horizontal: checked(self.horizontal.checked_add(n))?,
+                ..self
+            },
+            (true, Model::Aim) => Self {
+                horizontal: checked(self.horizontal.checked_add(n))?,
+                depth: policy.apply(
+                    checked(
+                        self.aim
+                            .checked_mul(n)
+                            .and_then(|d| self.depth.checked_add(d)),
+                    )?,
+                    line,
+                )?,
+                ..self
+            },
+            (false, Model::Direct) => Self {
+                depth: policy.apply(checked(self.depth.checked_add(n))?, line)?,
+                ..self
+            },
+            (false, Model::Aim) => Self {
+                aim: checked(self.aim.checked_add(n))?,
+                ..self
+            },
+        })
+    }
+
+    fn product(&self) -> Result<i64, CourseError> {
+        self.horizontal
+            .checked_mul(self.depth)
+            .ok_or(CourseError::Overflow(None))
+    }
+}
+
+fn navigate(input: &str, model: Model, policy: SurfacePolicy) -> Result<Position, CourseError> {
+    parse_course(input)?
+        .instructions()
+        .try_fold(Position::default(), |position, (line, instruction)| {
+            position.step(instruction, line, model, policy)
+        })
+}
+
+fn part1(input: &str, policy: SurfacePolicy) -> Result<i64, CourseError> {
+    navigate(input, Model::Direct, policy)?.product()
+}
+
+fn part2(input: &str, policy: SurfacePolicy) -> Result<i64, CourseError> {
+    navigate(input, Model::Aim, policy)?.product()
 }
 
 fn main() {
     let input = include_str!("input.txt");
-    println!("Part 1 = {}", part1(input));
-    println!("Part 2 = {}", part2(input));
+
+    let mut policy = SurfacePolicy::Error;
+    let mut model = Model::Aim;
+    let (mut csv, mut svg, mut target) = (None, None, None);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--clamp" => policy = SurfacePolicy::Clamp,
+            "--direct" => model = Model::Direct,
+            "--trace-csv" => csv = Some(args.next().expect("missing path for --trace-csv")),
+            "--trace-svg" => svg = Some(args.next().expect("missing path for --trace-svg")),
+            "--plan" => {
+                let arg = args.next().expect("missing target for --plan");
+                let (h, d) = arg
+                    .split_once(',')
+                    .expect("expected --plan HORIZONTAL,DEPTH");
+                target = Some((h.parse().unwrap(), d.parse().unwrap()));
+            }
+            "--encode" => {
+                let path = args.next().expect("missing path for --encode");
+                let course = parse_course(input).unwrap();
+                let instructions = course
+                    .instructions()
+                    .map(|(_, instruction)| instruction.clone())
+                    .collect::<Vec<_>>();
+                fs::write(path, encoding::encode(&instructions)).unwrap();
+                return;
+            }
+            "--decode" => {
+                let path = args.next().expect("missing path for --decode");
+                for instruction in encoding::decode(&fs::read(path).unwrap()).unwrap() {
+                    println!("{}", instruction);
+                }
+                return;
+            }
+            _ => panic!("unknown argument: {:?}", arg),
+        }
+    }
+
+    if let Some((horizontal, depth)) = target {
+        match planner::plan(horizontal, depth, model) {
+            Some(instructions) => print!("{}", planner::format_plan(&instructions)),
+            None => eprintln!("({}, {}) cannot be reached", horizontal, depth),
+        }
+        return;
+    }
+
+    if csv.is_some() || svg.is_some() {
+        let trace = trace::trace(input, model, policy);
+        if let Some(path) = csv {
+            fs::write(path, trace.to_csv()).unwrap();
+        }
+        if let Some(path) = svg {
+            fs::write(path, trace.to_svg()).unwrap();
+        }
+        if let Some(e) = trace.error {
+            eprintln!("Trace stopped early: {}", e);
+        }
+    }
+
+    println!("Part 1 = {}", part1(input, policy).unwrap());
+    println!("Part 2 = {}", part2(input, policy).unwrap());
 }
 
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
+    use proptest::prelude::*;
 
-    use crate::{part1, part2, Instruction};
+    use crate::{
+        parse_course, part1, part2, CourseError, Instruction, ParseInstructionError, SurfacePolicy,
+    };
 
     #[test]
     fn test_instruction_fromstr() {
@@ -95,6 +476,139 @@ mod tests {
 
         assert!("forward a".parse::<Instruction>().is_err());
         assert!("unknown 1".parse::<Instruction>().is_err());
+        assert_eq!(
+            "backward -3".parse::<Instruction>().unwrap(),
+            Instruction::Backward(-3)
+        );
+        assert_eq!(
+            "up +2 # ascend".parse::<Instruction>().unwrap(),
+            Instruction::Up(2)
+        );
+        assert_eq!(
+            "down 1 up 1".parse::<Instruction>(),
+            Err(ParseInstructionError::TrailingInput(1, "up".to_owned()))
+        );
+        assert_eq!(
+            "".parse::<Instruction>(),
+            Err(ParseInstructionError::UnexpectedEnd(1))
+        );
+    }
+
+    pub fn instruction() -> impl Strategy<Value = Instruction> {
+        prop_oneof![
+            any::<i64>().prop_map(Instruction::Forward),
+            any::<i64>().prop_map(Instruction::Backward),
+            any::<i64>().prop_map(Instruction::Up),
+            any::<i64>().prop_map(Instruction::Down),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_instruction_display_round_trip(instruction in instruction()) {
+            prop_assert_eq!(instruction.to_string().parse::<Instruction>(), Ok(instruction));
+        }
+
+        #[test]
+        fn test_instruction_serde_round_trip(instruction in instruction()) {
+            let json = serde_json::to_string(&instruction).unwrap();
+            prop_assert_eq!(serde_json::from_str::<Instruction>(&json).unwrap(), instruction);
+        }
+    }
+
+    #[test]
+    fn test_instruction_serde() {
+        assert_eq!(
+            serde_json::to_string(&Instruction::Backward(-3)).unwrap(),
+            r#"{"backward":-3}"#
+        );
+    }
+
+    #[test]
+    fn test_parse_course() {
+        let input = indoc! {"
+            # Dive, then zig-zag.
+            down 2
+
+            repeat 2 {
+                forward 3 repeat 2 {up 1}
+                backward -1
+            }
+        "};
+        let course = parse_course(input).unwrap();
+        assert_eq!(course.len, 9);
+        let instructions = course
+            .instructions()
+            .map(|(line, instruction)| (line, instruction.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            instructions,
+            [
+                (2, Instruction::Down(2)),
+                (5, Instruction::Forward(3)),
+                (5, Instruction::Up(1)),
+                (5, Instruction::Up(1)),
+                (6, Instruction::Backward(-1)),
+                (5, Instruction::Forward(3)),
+                (5, Instruction::Up(1)),
+                (5, Instruction::Up(1)),
+                (6, Instruction::Backward(-1)),
+            ]
+        );
+        assert_eq!(part1(input, SurfacePolicy::Clamp), Ok(0));
+        assert_eq!(part2(input, SurfacePolicy::Error), Ok(8 * 4));
+    }
+
+    #[test]
+    fn test_parse_course_errors() {
+        let errors = [
+            (
+                "forward 1\nforward\n",
+                ParseInstructionError::MissingValue(2, "forward".to_owned()),
+            ),
+            (
+                "\nsideways 1",
+                ParseInstructionError::UnknownAction(2, "sideways".to_owned()),
+            ),
+            (
+                "repeat 2 forward 1",
+                ParseInstructionError::ExpectedBrace(1, "forward".to_owned()),
+            ),
+            (
+                "repeat 2 {\nforward 1\n",
+                ParseInstructionError::UnclosedRepeat(1),
+            ),
+            ("forward 1\n}\n", ParseInstructionError::UnmatchedBrace(2)),
+            (
+                "repeat {",
+                ParseInstructionError::MissingValue(1, "repeat".to_owned()),
+            ),
+        ];
+        for (input, error) in errors {
+            assert_eq!(parse_course(input), Err(error));
+        }
+
+        assert_eq!(
+            parse_course(&"repeat 1 {".repeat(200_000)),
+            Err(ParseInstructionError::TooDeep(1))
+        );
+        assert_eq!(
+            parse_course("down 1\nrepeat 1000000000000 { forward 1 }"),
+            Err(ParseInstructionError::TooLong(2))
+        );
+        assert_eq!(
+            parse_course("repeat 4294967296 {\nrepeat 4294967296 { up 1 }\n}"),
+            Err(ParseInstructionError::TooLong(1))
+        );
+        assert_eq!(
+            parse_course("repeat 18446744073709551615 { }").map(|course| course.len),
+            Ok(0)
+        );
+
+        assert!(matches!(
+            parse_course("down 1\nrepeat -1 { up 1 }"),
+            Err(ParseInstructionError::ParseIntError(2, _, s)) if s == "-1"
+        ));
     }
 
     #[test]
@@ -107,7 +621,7 @@ mod tests {
             down 8
             forward 2
         "};
-        assert_eq!(part1(input), 150);
+        assert_eq!(part1(input, SurfacePolicy::Error), Ok(150));
     }
 
     #[test]
@@ -120,6 +634,53 @@ mod tests {
             down 8
             forward 2
         "};
-        assert_eq!(part2(input), 900);
+        assert_eq!(part2(input, SurfacePolicy::Error), Ok(900));
+    }
+
+    #[test]
+    fn test_surface_policy() {
+        let input = indoc! {"
+            forward 2
+            up 3
+            down 4
+        "};
+        assert_eq!(part1(input, SurfacePolicy::Clamp), Ok(8));
+        assert_eq!(
+            part1(input, SurfacePolicy::Error),
+            Err(CourseError::AboveSurface { line: 2, depth: -3 })
+        );
+
+        let input = indoc! {"
+            up 1
+            forward 2
+            down 3
+            forward 1
+        "};
+        assert_eq!(part2(input, SurfacePolicy::Clamp), Ok(6));
+        assert_eq!(
+            part2(input, SurfacePolicy::Error),
+            Err(CourseError::AboveSurface { line: 2, depth: -2 })
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        let input = "up -9223372036854775808\n";
+        assert_eq!(
+            part1(input, SurfacePolicy::Error),
+            Err(CourseError::Overflow(Some(1)))
+        );
+
+        let input = "down 4294967296\nforward 4294967296\n";
+        assert_eq!(
+            part2(input, SurfacePolicy::Error),
+            Err(CourseError::Overflow(Some(2)))
+        );
+
+        let input = "down 1\nforward 4294967296\n";
+        assert_eq!(
+            part2(input, SurfacePolicy::Error),
+            Err(CourseError::Overflow(None))
+        );
     }
 }
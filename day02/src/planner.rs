@@ -0,0 +1,115 @@
+use crate::{Instruction, Model};
+
+/// Builds a course with the fewest possible instructions that ends at `(horizontal, depth)`.
+/// Magnitudes are always positive (`backward` and `up` are used for negative directions) and the
+/// submarine never goes above the surface on the way. Returns `None` for targets above the
+/// surface, which no legal course reaches, and for `horizontal == i64::MIN`, whose distance has no
+/// positive `i64` magnitude.
+pub fn plan(horizontal: i64, depth: i64, model: Model) -> Option<Vec<Instruction>> {
+    if depth < 0 {
+        return None;
+    }
+
+    Some(match (model, horizontal, depth) {
+        (_, 0, 0) => vec![],
+        (_, h, 0) => vec![horizontal_move(h)?],
+        (Model::Direct, 0, d) => vec![Instruction::Down(d)],
+        (Model::Direct, h, d) => vec![horizontal_move(h)?, Instruction::Down(d)],
+        // Depth only changes while moving, so out and back with the aim flipped in between. Two
+        // horizontal moves at the same aim would cancel out, hence three instructions.
+        (Model::Aim, 0, d) => vec![
+            Instruction::Forward(1),
+            Instruction::Up(d),
+            Instruction::Backward(1),
+        ],
+        (Model::Aim, h, d) if d % h == 0 => vec![aim_move(d / h)?, horizontal_move(h)?],
+        // Travel most of the way level, then cover the last unit with the aim set to the depth.
+        (Model::Aim, h, d) => {
+            let s = h.signum();
+            vec![
+                horizontal_move(h - s)?,
+                aim_move(d * s)?,
+                horizontal_move(s)?,
+            ]
+        }
+    })
+}
+
+fn horizontal_move(n: i64) -> Option<Instruction> {
+    if n < 0 {
+        n.checked_neg().map(Instruction::Backward)
+    } else {
+        Some(Instruction::Forward(n))
+    }
+}
+
+fn aim_move(n: i64) -> Option<Instruction> {
+    if n < 0 {
+        n.checked_neg().map(Instruction::Up)
+    } else {
+        Some(Instruction::Down(n))
+    }
+}
+
+/// Writes a plan in the format read by `parse_course`, one instruction per line.
+pub fn format_plan(instructions: &[Instruction]) -> String {
+    instructions
+        .iter()
+        .map(|instruction| format!("{}\n", instruction))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{navigate, planner::*, Position, SurfacePolicy};
+
+    #[test]
+    fn test_plan_round_trip() {
+        for model in [Model::Direct, Model::Aim] {
+            for horizontal in -12..=12 {
+                for depth in 0..=30 {
+                    let course = format_plan(&plan(horizontal, depth, model).unwrap());
+                    let Position {
+                        horizontal: h,
+                        depth: d,
+                        ..
+                    } = navigate(&course, model, SurfacePolicy::Error).unwrap();
+                    assert_eq!((h, d), (horizontal, depth), "{:?}: {:?}", model, course);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_plan_length() {
+        assert_eq!(plan(15, 60, Model::Aim).unwrap().len(), 2);
+        assert_eq!(plan(15, 61, Model::Aim).unwrap().len(), 3);
+        assert_eq!(plan(0, 61, Model::Aim).unwrap().len(), 3);
+        assert_eq!(plan(-4, 0, Model::Aim).unwrap().len(), 1);
+        assert_eq!(plan(15, 10, Model::Direct).unwrap().len(), 2);
+        assert_eq!(plan(15, -1, Model::Direct), None);
+    }
+
+    #[test]
+    fn test_plan_extremes() {
+        for model in [Model::Direct, Model::Aim] {
+            assert_eq!(plan(i64::MIN, 0, model), None);
+            assert_eq!(plan(i64::MIN, 5, model).is_some(), model == Model::Aim);
+            let course = format_plan(&plan(i64::MIN + 1, i64::MAX, model).unwrap());
+            let Position {
+                horizontal: h,
+                depth: d,
+                ..
+            } = navigate(&course, model, SurfacePolicy::Error).unwrap();
+            assert_eq!((h, d), (i64::MIN + 1, i64::MAX));
+        }
+    }
+
+    #[test]
+    fn test_format_plan() {
+        assert_eq!(
+            format_plan(&plan(-7, 3, Model::Aim).unwrap()),
+            "backward 6\nup 3\nbackward 1\n"
+        );
+    }
+}
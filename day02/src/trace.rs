@@ -0,0 +1,163 @@
+use std::fmt::Write;
+
+use crate::{parse_course, CourseError, Model, Position, SurfacePolicy};
+
+/// The submarine's state right after executing the instruction written on `line`.
+#[derive(Debug, PartialEq)]
+pub struct Step {
+    pub line: usize,
+    pub position: Position,
+}
+
+/// Every intermediate state of a course. A course that fails part way keeps the steps leading up
+/// to the failure, so the route can still be inspected.
+#[derive(Debug, PartialEq)]
+pub struct Trace {
+    pub steps: Vec<Step>,
+    pub error: Option<CourseError>,
+}
+
+pub fn trace(input: &str, model: Model, policy: SurfacePolicy) -> Trace {
+    let course = match parse_course(input) {
+        Ok(course) => course,
+        Err(e) => {
+            return Trace {
+                steps: Vec::new(),
+                error: Some(e.into()),
+            }
+        }
+    };
+
+    let mut steps = Vec::new();
+    let mut position = Position::default();
+    for (line, instruction) in course.instructions() {
+        match position.step(instruction, line, model, policy) {
+            Ok(p) => position = p,
+            Err(e) => {
+                return Trace {
+                    steps,
+                    error: Some(e),
+                }
+            }
+        }
+        steps.push(Step { line, position });
+    }
+
+    Trace { steps, error: None }
+}
+
+impl Trace {
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("step,line,horizontal,depth,aim\n");
+        for (i, Step { line, position }) in self.steps.iter().enumerate() {
+            let Position {
+                horizontal,
+                depth,
+                aim,
+            } = position;
+            writeln!(csv, "{},{},{},{},{}", i + 1, line, horizontal, depth, aim).unwrap();
+        }
+        csv
+    }
+
+    /// Plots depth (growing downwards, like the sea) against horizontal distance. The view box is
+    /// fitted to the route, with the surface drawn as a blue line for reference. Its size is taken
+    /// in `i128`, as a route can span more than an `i64`.
+    pub fn to_svg(&self) -> String {
+        let points = std::iter::once(Position::default())
+            .chain(self.steps.iter().map(|step| step.position))
+            .map(|p| (p.horizontal, p.depth))
+            .collect::<Vec<_>>();
+
+        let min_x = points.iter().map(|p| p.0).min().unwrap();
+        let max_x = points.iter().map(|p| p.0).max().unwrap();
+        let min_y = points.iter().map(|p| p.1).min().unwrap();
+        let max_y = points.iter().map(|p| p.1).max().unwrap();
+        let width = (i128::from(max_x) - i128::from(min_x)).max(1);
+        let height = (i128::from(max_y) - i128::from(min_y)).max(1);
+
+        let path = points
+            .iter()
+            .enumerate()
+            .map(|(i, (x, y))| format!("{}{} {}", if i == 0 { 'M' } else { 'L' }, x, y))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="800" height="400" viewBox="{} {} {} {}" preserveAspectRatio="none">"#,
+            min_x, min_y, width, height
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"  <line x1="{}" y1="0" x2="{}" y2="0" stroke="steelblue" vector-effect="non-scaling-stroke"/>"#,
+            min_x,
+            i128::from(min_x) + width
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"  <path d="{}" fill="none" stroke="black" vector-effect="non-scaling-stroke"/>"#,
+            path
+        )
+        .unwrap();
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{trace::*, CourseError};
+
+    const INPUT: &str = indoc! {"
+        forward 5
+        down 5
+        forward 8
+    "};
+
+    #[test]
+    fn test_trace_csv() {
+        let trace = trace(INPUT, Model::Aim, SurfacePolicy::Error);
+        assert_eq!(trace.error, None);
+        assert_eq!(
+            trace.to_csv(),
+            indoc! {"
+                step,line,horizontal,depth,aim
+                1,1,5,0,0
+                2,2,5,0,5
+                3,3,13,40,5
+            "}
+        );
+    }
+
+    #[test]
+    fn test_trace_svg() {
+        let svg = trace(INPUT, Model::Direct, SurfacePolicy::Error).to_svg();
+        assert!(svg.contains(r#"viewBox="0 0 13 5""#));
+        assert!(svg.contains(r#"d="M0 0 L5 0 L5 5 L13 5""#));
+
+        let input = "forward 9223372036854775807\nbackward 9223372036854775807\nbackward 9223372036854775807\n";
+        let svg = trace(input, Model::Direct, SurfacePolicy::Error).to_svg();
+        assert!(svg.contains(r#"viewBox="-9223372036854775807 0 18446744073709551614 1""#));
+        assert!(svg.contains(r#"x2="9223372036854775807""#));
+    }
+
+    #[test]
+    fn test_trace_stops_at_error() {
+        let trace = trace(
+            "forward 1\nup 2\nforward 3\n",
+            Model::Direct,
+            SurfacePolicy::Error,
+        );
+        assert_eq!(trace.steps.len(), 1);
+        assert_eq!(
+            trace.error,
+            Some(CourseError::AboveSurface { line: 2, depth: -2 })
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::{filter, BitCriteria, Report, Word};
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Step {
+    pub column: usize,
+    pub ones: usize,
+    pub zeros: usize,
+    /// The bit the candidates were filtered on, or `None` if a tie kept them all.
+    pub kept: Option<u8>,
+    /// A tie error ended the filter here, so nothing was kept.
+    pub stopped: bool,
+    pub remaining: Vec<String>,
+}
+
+/// How a rating was reached, column by column, for checking hand calculations.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Explanation {
+    pub steps: Vec<Step>,
+    /// The selected line, if the filter got down to exactly one.
+    pub line: Option<String>,
+    pub rating: Option<String>,
+    pub error: Option<String>,
+}
+
+pub fn explain_rating<W: Word>(input: &str, criteria: &BitCriteria) -> Explanation {
+    let report = match Report::<W>::parse(input) {
+        Ok(report) => report,
+        Err(e) => {
+            return Explanation {
+                steps: Vec::new(),
+                line: None,
+                rating: None,
+                error: Some(e.to_string()),
+            }
+        }
+    };
+    let width = report.width;
+    let to_line = |w: &W| format!("{:0width$b}", w.to_biguint(), width = width);
+
+    let mut steps = Vec::new();
+    let result = filter(report.words, width, criteria, |filter, words| {
+        steps.push(Step {
+            column: filter.column,
+            ones: filter.ones,
+            zeros: filter.zeros,
+            kept: filter.kept.map(u8::from),
+            stopped: filter.stopped,
+            remaining: words.iter().map(to_line).collect(),
+        })
+    });
+
+    match result {
+        Ok(word) => Explanation {
+            steps,
+            line: Some(to_line(&word)),
+            rating: Some(word.to_biguint().to_string()),
+            error: None,
+        },
+        Err(e) => Explanation {
+            steps,
+            line: None,
+            rating: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for step in &self.steps {
+            let kept = match step.kept {
+                _ if step.stopped => "tie, stopped".to_owned(),
+                Some(bit) => format!("keep {}", bit),
+                None => "keep both".to_owned(),
+            };
+            writeln!(
+                f,
+                "column {}: {} ones, {} zeros, {}, {} left",
+                step.column,
+                step.ones,
+                step.zeros,
+                kept,
+                step.remaining.len()
+            )?;
+            for line in &step.remaining {
+                writeln!(f, "    {}", line)?;
+            }
+        }
+        match (&self.line, &self.rating, &self.error) {
+            (Some(line), Some(rating), _) => writeln!(f, "rating = {} ({})", line, rating),
+            (_, _, Some(error)) => writeln!(f, "failed: {}", error),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{explain::*, tests::INPUT, FilterError, TiePolicy};
+
+    #[test]
+    fn test_explain_oxygen() {
+        let explanation = explain_rating::<u64>(INPUT, &BitCriteria::OXYGEN);
+        assert_eq!(explanation.steps.len(), 5);
+        assert_eq!(
+            explanation.to_string(),
+            indoc! {"
+                column 0: 7 ones, 5 zeros, keep 1, 7 left
+                    11110
+                    10110
+                    10111
+                    10101
+                    11100
+                    10000
+                    11001
+                column 1: 3 ones, 4 zeros, keep 0, 4 left
+                    10110
+                    10111
+                    10101
+                    10000
+                column 2: 3 ones, 1 zeros, keep 1, 3 left
+                    10110
+                    10111
+                    10101
+                column 3: 2 ones, 1 zeros, keep 1, 2 left
+                    10110
+                    10111
+                column 4: 1 ones, 1 zeros, keep 1, 1 left
+                    10111
+                rating = 10111 (23)
+            "}
+        );
+    }
+
+    #[test]
+    fn test_explain_tie_error() {
+        let mut criteria = BitCriteria::OXYGEN;
+        criteria.tie = TiePolicy::Error;
+        let explanation = explain_rating::<u64>("110\n101\n100\n011\n", &criteria);
+        assert_eq!(
+            explanation.to_string(),
+            indoc! {"
+                column 0: 3 ones, 1 zeros, keep 1, 3 left
+                    110
+                    101
+                    100
+                column 1: 1 ones, 2 zeros, keep 0, 2 left
+                    101
+                    100
+                column 2: 1 ones, 1 zeros, tie, stopped, 2 left
+                    101
+                    100
+                failed: tie in column 2
+            "}
+        );
+        assert!(explanation.steps[2].stopped);
+        assert!(explanation.steps[..2].iter().all(|step| !step.stopped));
+    }
+
+    #[test]
+    fn test_explain_json() {
+        let explanation = explain_rating::<u64>("10\n11\n", &BitCriteria::CO2);
+        assert_eq!(
+            explanation.error,
+            Some(FilterError::Emptied { column: 0 }.to_string())
+        );
+        assert_eq!(
+            explain_rating::<u64>("10\n1\n", &BitCriteria::CO2).to_string(),
+            "failed: line 2 is 1 bits wide, but the widest line has 2\n"
+        );
+        assert_eq!(
+            serde_json::to_string(&explanation).unwrap(),
+            r#"{"steps":[{"column":0,"ones":2,"zeros":0,"kept":0,"stopped":false,"remaining":[]}],"line":null,"rating":null,"error":"no lines left after column 0"}"#
+        );
+    }
+}
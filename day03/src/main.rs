@@ -1,58 +1,457 @@
-fn part1(input: &str) -> u32 {
-    let lines = input.lines().collect::<Vec<&str>>();
-    let columns = lines[0].len();
+mod explain;
 
-    let mut gamma = 0;
-    for column in 0..columns {
+use std::{error, fmt};
+
+use explain::explain_rating;
+use num_bigint::BigUint;
+
+/// A diagnostic line packed into an integer, with bit `i` holding the `i`-th character counted
+/// from the right.
+trait Word: Clone {
+    /// Parses a line of `0`s and `1`s, or returns `None` if it is not binary or too wide.
+    fn parse(s: &str) -> Option<Self>;
+    fn bit(&self, i: usize) -> bool;
+    fn to_biguint(&self) -> BigUint;
+}
+
+macro_rules! impl_word {
+    ($($t:ty),*) => {$(
+        impl Word for $t {
+            fn parse(s: &str) -> Option<Self> {
+                <$t>::from_str_radix(s, 2).ok()
+            }
+
+            fn bit(&self, i: usize) -> bool {
+                self >> i & 1 == 1
+            }
+
+            fn to_biguint(&self) -> BigUint {
+                BigUint::from(*self)
+            }
+        }
+    )*};
+}
+
+impl_word!(u64, u128);
+
+/// A word of any width, stored as little-endian 64-bit limbs.
+#[derive(Debug, Clone, PartialEq)]
+struct BitSet(Vec<u64>);
+
+impl Word for BitSet {
+    fn parse(s: &str) -> Option<Self> {
+        let mut limbs = vec![0; s.len().div_ceil(64)];
+        for (i, c) in s.bytes().rev().enumerate() {
+            match c {
+                b'0' => {}
+                b'1' => limbs[i / 64] |= 1 << (i % 64),
+                _ => return None,
+            }
+        }
+        Some(Self(limbs))
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        self.0[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        BigUint::from_bytes_le(
+            &self
+                .0
+                .iter()
+                .flat_map(|limb| limb.to_le_bytes())
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+/// A diagnostic report parsed once into packed words. The first character of a line ends up in
+/// bit `width - 1`, so column `i` of the text is bit `width - 1 - i` of the word.
+struct Report<W> {
+    width: usize,
+    words: Vec<W>,
+}
+
+impl<W: Word> Report<W> {
+    /// Every line must be as wide as the widest one.
+    fn parse(input: &str) -> Result<Self, ReportError> {
+        let width = width(input);
+        let words = input
+            .lines()
+            .enumerate()
+            .map(|(i, s)| {
+                let line = i + 1;
+                if !s.bytes().all(|c| c == b'0' || c == b'1') {
+                    Err(ReportError::NotBinary { line })
+                } else if s.len() != width {
+                    Err(ReportError::Ragged {
+                        line,
+                        width: s.len(),
+                        expected: width,
+                    })
+                } else {
+                    W::parse(s).ok_or(ReportError::TooWide { width })
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { width, words })
+    }
+}
+
+/// The widest line of the input, which picks the word type.
+fn width(input: &str) -> usize {
+    input.lines().map(str::len).max().unwrap_or(0)
+}
+
+/// Lines are counted from 1.
+#[derive(Debug, PartialEq)]
+enum ReportError {
+    NotBinary {
+        line: usize,
+    },
+    Ragged {
+        line: usize,
+        width: usize,
+        expected: usize,
+    },
+    /// The lines are wider than the word type holds.
+    TooWide {
+        width: usize,
+    },
+}
+
+impl fmt::Display for ReportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotBinary { line } => write!(f, "line {} is not a binary number", line),
+            Self::Ragged {
+                line,
+                width,
+                expected,
+            } => write!(
+                f,
+                "line {} is {} bits wide, but the widest line has {}",
+                line, width, expected
+            ),
+            Self::TooWide { width } => write!(f, "{}-bit lines are too wide", width),
+        }
+    }
+}
+
+impl error::Error for ReportError {}
+
+fn ones<W: Word>(words: &[W], bit: usize) -> usize {
+    words.iter().filter(|w| w.bit(bit)).count()
+}
+
+#[derive(Debug, PartialEq)]
+struct PowerReport {
+    gamma: BigUint,
+    epsilon: BigUint,
+}
+
+impl PowerReport {
+    /// The submarine's power consumption.
+    fn rating(&self) -> BigUint {
+        &self.gamma * &self.epsilon
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct LifeSupportReport {
+    oxygen: BigUint,
+    co2: BigUint,
+}
+
+impl LifeSupportReport {
+    /// The life support rating.
+    fn rating(&self) -> BigUint {
+        &self.oxygen * &self.co2
+    }
+}
+
+fn power<W: Word>(input: &str) -> Result<PowerReport, ReportError> {
+    let report = Report::<W>::parse(input)?;
+
+    let mut gamma = BigUint::default();
+    for bit in (0..report.width).rev() {
         gamma <<= 1;
-        let ones = lines
-            .iter()
-            .map(|s| s.chars().nth(column).unwrap())
-            .filter(|&c| c == '1')
-            .count();
-        if ones >= lines.len() - ones {
-            gamma += 1;
+        let ones = ones(&report.words, bit);
+        if ones >= report.words.len() - ones {
+            gamma += 1u32;
         }
     }
 
-    let epsilon = !gamma & ((1 << columns) - 1);
-    gamma * epsilon
+    let epsilon = &gamma ^ ((BigUint::from(1u32) << report.width) - 1u32);
+    Ok(PowerReport { gamma, epsilon })
 }
 
-fn part2(input: &str, most: bool) -> u32 {
-    let mut lines = input.lines().collect::<Vec<&str>>();
-    let mut column = 0;
-    while lines.len() > 1 {
-        let ones = lines
-            .iter()
-            .map(|s| s.chars().nth(column).unwrap())
-            .filter(|&c| c == '1')
-            .count();
-        if ones >= (lines.len() - ones) {
-            lines.retain(|x| x.chars().nth(column).unwrap() == if most { '1' } else { '0' })
-        } else {
-            lines.retain(|x| x.chars().nth(column).unwrap() == if most { '0' } else { '1' })
+/// What to keep when a column has as many ones as zeros.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TiePolicy {
+    PreferOne,
+    PreferZero,
+    /// Keep every candidate and move on to the next column.
+    KeepBoth,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnOrder {
+    LeftToRight,
+    RightToLeft,
+}
+
+/// How `rating` narrows the report down to a single line: keep the lines holding the most (or
+/// least) common bit of each column, visiting the columns in `order`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BitCriteria {
+    most_common: bool,
+    tie: TiePolicy,
+    order: ColumnOrder,
+}
+
+impl BitCriteria {
+    const OXYGEN: Self = Self {
+        most_common: true,
+        tie: TiePolicy::PreferOne,
+        order: ColumnOrder::LeftToRight,
+    };
+    const CO2: Self = Self {
+        most_common: false,
+        tie: TiePolicy::PreferZero,
+        order: ColumnOrder::LeftToRight,
+    };
+
+    /// The bit to keep in a column, `None` to keep both.
+    fn keep(&self, ones: usize, zeros: usize, column: usize) -> Result<Option<bool>, FilterError> {
+        if ones == zeros {
+            return match self.tie {
+                TiePolicy::PreferOne => Ok(Some(true)),
+                TiePolicy::PreferZero => Ok(Some(false)),
+                TiePolicy::KeepBoth => Ok(None),
+                TiePolicy::Error => Err(FilterError::Tie { column }),
+            };
         }
+        Ok(Some((ones > zeros) == self.most_common))
+    }
+}
+
+/// Columns are counted from the left of the line, starting at 0.
+#[derive(Debug, PartialEq)]
+enum FilterError {
+    Report(ReportError),
+    EmptyReport,
+    Emptied {
+        column: usize,
+    },
+    Tie {
+        column: usize,
+    },
+    /// Every column was used up with more than one line left.
+    Unresolved {
+        remaining: usize,
+    },
+}
 
-        column += 1;
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Report(e) => e.fmt(f),
+            Self::EmptyReport => write!(f, "the report has no lines"),
+            Self::Emptied { column } => write!(f, "no lines left after column {}", column),
+            Self::Tie { column } => write!(f, "tie in column {}", column),
+            Self::Unresolved { remaining } => {
+                write!(f, "{} lines left after the last column", remaining)
+            }
+        }
+    }
+}
+
+impl error::Error for FilterError {}
+
+impl From<ReportError> for FilterError {
+    fn from(e: ReportError) -> Self {
+        Self::Report(e)
     }
-    u32::from_str_radix(lines[0], 2).unwrap()
+}
+
+/// The outcome of filtering on one column. `kept` is `None` when a tie kept both bits, or when
+/// a tie error `stopped` the filter before anything was kept.
+#[derive(Debug, PartialEq)]
+struct ColumnFilter {
+    column: usize,
+    ones: usize,
+    zeros: usize,
+    kept: Option<bool>,
+    stopped: bool,
+}
+
+/// Narrows `words` down to a single one, calling `on_column` with the candidates left after
+/// each column. A column that stops on a tie error is reported too, marked as `stopped`.
+fn filter<W: Word>(
+    mut words: Vec<W>,
+    width: usize,
+    criteria: &BitCriteria,
+    mut on_column: impl FnMut(&ColumnFilter, &[W]),
+) -> Result<W, FilterError> {
+    if words.is_empty() {
+        return Err(FilterError::EmptyReport);
+    }
+
+    let columns: Box<dyn Iterator<Item = usize>> = match criteria.order {
+        ColumnOrder::LeftToRight => Box::new(0..width),
+        ColumnOrder::RightToLeft => Box::new((0..width).rev()),
+    };
+    for column in columns {
+        if words.len() == 1 {
+            break;
+        }
+        let bit = width - 1 - column;
+        let ones = ones(&words, bit);
+        let zeros = words.len() - ones;
+        let kept = match criteria.keep(ones, zeros, column) {
+            Ok(kept) => kept,
+            Err(e) => {
+                let filter = ColumnFilter {
+                    column,
+                    ones,
+                    zeros,
+                    kept: None,
+                    stopped: true,
+                };
+                on_column(&filter, &words);
+                return Err(e);
+            }
+        };
+        if let Some(keep) = kept {
+            words.retain(|w| w.bit(bit) == keep);
+        }
+        on_column(
+            &ColumnFilter {
+                column,
+                ones,
+                zeros,
+                kept,
+                stopped: false,
+            },
+            &words,
+        );
+        if words.is_empty() {
+            return Err(FilterError::Emptied { column });
+        }
+    }
+
+    match words.len() {
+        1 => Ok(words.pop().unwrap()),
+        remaining => Err(FilterError::Unresolved { remaining }),
+    }
+}
+
+fn rating<W: Word>(input: &str, criteria: &BitCriteria) -> Result<BigUint, FilterError> {
+    let report = Report::<W>::parse(input)?;
+    filter(report.words, report.width, criteria, |_, _| {}).map(|w| w.to_biguint())
+}
+
+/// Picks the narrowest word type that holds every line of the input.
+macro_rules! dispatch {
+    ($f:ident($input:expr $(, $arg:expr)*)) => {
+        match width($input) {
+            0..=64 => $f::<u64>($input $(, $arg)*),
+            65..=128 => $f::<u128>($input $(, $arg)*),
+            _ => $f::<BitSet>($input $(, $arg)*),
+        }
+    };
+}
+
+fn part1(input: &str) -> Result<PowerReport, ReportError> {
+    dispatch!(power(input))
+}
+
+fn part2(
+    input: &str,
+    oxygen: &BitCriteria,
+    co2: &BitCriteria,
+) -> Result<LifeSupportReport, FilterError> {
+    Ok(LifeSupportReport {
+        oxygen: dispatch!(rating(input, oxygen))?,
+        co2: dispatch!(rating(input, co2))?,
+    })
 }
 
 fn main() {
     let input = include_str!("input.txt");
 
-    println!("Part 1 = {}", part1(input));
-    println!("Part 2 = {}", part2(input, true) + part2(input, false));
+    let (mut oxygen, mut co2) = (BitCriteria::OXYGEN, BitCriteria::CO2);
+    let (mut explain, mut json) = (None, false);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tie" => {
+                let tie = match args.next().as_deref() {
+                    Some("one") => TiePolicy::PreferOne,
+                    Some("zero") => TiePolicy::PreferZero,
+                    Some("both") => TiePolicy::KeepBoth,
+                    Some("error") => TiePolicy::Error,
+                    _ => panic!("expected --tie one|zero|both|error"),
+                };
+                oxygen.tie = tie;
+                co2.tie = tie;
+            }
+            "--right-to-left" => {
+                oxygen.order = ColumnOrder::RightToLeft;
+                co2.order = ColumnOrder::RightToLeft;
+            }
+            "--explain" => explain = args.next(),
+            "--json" => json = true,
+            _ => panic!("unknown argument: {:?}", arg),
+        }
+    }
+
+    if let Some(rating) = explain {
+        let criteria = match rating.as_str() {
+            "oxygen" => &oxygen,
+            "co2" => &co2,
+            _ => panic!("expected --explain oxygen|co2"),
+        };
+        let explanation = dispatch!(explain_rating(input, criteria));
+        if json {
+            println!("{}", serde_json::to_string_pretty(&explanation).unwrap());
+        } else {
+            print!("{}", explanation);
+        }
+        return;
+    }
+
+    match part1(input) {
+        Ok(power) => println!(
+            "Part 1 = {} (gamma = {}, epsilon = {})",
+            power.rating(),
+            power.gamma,
+            power.epsilon
+        ),
+        Err(e) => eprintln!("Part 1 failed: {}", e),
+    }
+
+    match part2(input, &oxygen, &co2) {
+        Ok(life_support) => println!(
+            "Part 2 = {} (oxygen = {}, CO2 = {})",
+            life_support.rating(),
+            life_support.oxygen,
+            life_support.co2
+        ),
+        Err(e) => eprintln!("Part 2 failed: {}", e),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
 
-    use crate::{part1, part2};
+    use crate::*;
 
-    const INPUT: &str = indoc! {"
+    pub const INPUT: &str = indoc! {"
         00100
         11110
         10110
@@ -67,13 +466,154 @@ mod tests {
         01010
     "};
 
+    #[test]
+    fn test_report_parse() {
+        let report = Report::<u64>::parse(INPUT).unwrap();
+        assert_eq!(report.width, 5);
+        assert_eq!(report.words[..3], [0b00100, 0b11110, 0b10110]);
+    }
+
+    #[test]
+    fn test_report_errors() {
+        assert_eq!(
+            part1("10101\n1\n00000\n").err(),
+            Some(ReportError::Ragged {
+                line: 2,
+                width: 1,
+                expected: 5,
+            })
+        );
+        let wide = "1".repeat(130);
+        assert_eq!(
+            part1(&format!("{}\n1\n", wide)).err(),
+            Some(ReportError::Ragged {
+                line: 2,
+                width: 1,
+                expected: 130,
+            })
+        );
+        assert_eq!(
+            part2(
+                &format!("1\n{}\n", wide),
+                &BitCriteria::OXYGEN,
+                &BitCriteria::CO2
+            ),
+            Err(FilterError::Report(ReportError::Ragged {
+                line: 1,
+                width: 1,
+                expected: 130,
+            }))
+        );
+        assert_eq!(
+            part1("101\n1x1\n").err(),
+            Some(ReportError::NotBinary { line: 2 })
+        );
+        assert_eq!(
+            power::<u64>(&wide).err(),
+            Some(ReportError::TooWide { width: 130 })
+        );
+    }
+
     #[test]
     fn test_part1() {
-        assert_eq!(part1(INPUT), 198);
+        let power = part1(INPUT).unwrap();
+        assert_eq!(power.gamma, BigUint::from(22u32));
+        assert_eq!(power.epsilon, BigUint::from(9u32));
+        assert_eq!(power.rating(), BigUint::from(198u32));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(INPUT, true) * part2(INPUT, false), 230);
+        let life_support = part2(INPUT, &BitCriteria::OXYGEN, &BitCriteria::CO2).unwrap();
+        assert_eq!(life_support.oxygen, BigUint::from(23u32));
+        assert_eq!(life_support.co2, BigUint::from(10u32));
+        assert_eq!(life_support.rating(), BigUint::from(230u32));
+    }
+
+    #[test]
+    fn test_word_types_agree() {
+        assert_eq!(power::<u128>(INPUT), power::<u64>(INPUT));
+        assert_eq!(power::<BitSet>(INPUT), power::<u64>(INPUT));
+        for criteria in [BitCriteria::OXYGEN, BitCriteria::CO2] {
+            assert_eq!(
+                rating::<BitSet>(INPUT, &criteria),
+                rating::<u64>(INPUT, &criteria)
+            );
+        }
+    }
+
+    #[test]
+    fn test_wide_words() {
+        // Repeating every line keeps each column's counts, so the answers repeat the 5-bit ones.
+        let repeated = |n, bits: u32| (0..n).fold(BigUint::default(), |acc, _| (acc << 5) + bits);
+        for n in [13, 26, 40] {
+            let input = INPUT
+                .lines()
+                .map(|s| s.repeat(n) + "\n")
+                .collect::<String>();
+            assert_eq!(
+                part1(&input),
+                Ok(PowerReport {
+                    gamma: repeated(n, 0b10110),
+                    epsilon: repeated(n, 0b01001),
+                })
+            );
+            assert_eq!(
+                part2(&input, &BitCriteria::OXYGEN, &BitCriteria::CO2),
+                Ok(LifeSupportReport {
+                    oxygen: repeated(n, 0b10111),
+                    co2: repeated(n, 0b01010),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_bit_criteria() {
+        let criteria = |tie, order| BitCriteria {
+            most_common: true,
+            tie,
+            order,
+        };
+
+        // Left to right, column 2 is a tie between 110 and 111.
+        let input = "110\n111\n000\n";
+        let rating = |c| rating::<u64>(input, &c).map(|r| r.to_string());
+        assert_eq!(
+            rating(criteria(TiePolicy::PreferOne, ColumnOrder::LeftToRight)),
+            Ok("7".to_owned())
+        );
+        assert_eq!(
+            rating(criteria(TiePolicy::PreferZero, ColumnOrder::LeftToRight)),
+            Ok("6".to_owned())
+        );
+        assert_eq!(
+            rating(criteria(TiePolicy::Error, ColumnOrder::LeftToRight)),
+            Err(FilterError::Tie { column: 2 })
+        );
+        assert_eq!(
+            rating(criteria(TiePolicy::KeepBoth, ColumnOrder::LeftToRight)),
+            Err(FilterError::Unresolved { remaining: 2 })
+        );
+        assert_eq!(
+            rating(criteria(TiePolicy::PreferZero, ColumnOrder::RightToLeft)),
+            Ok("0".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_filter_errors() {
+        assert_eq!(
+            rating::<u64>("", &BitCriteria::OXYGEN),
+            Err(FilterError::EmptyReport)
+        );
+        assert_eq!(
+            rating::<u64>("10\n11\n", &BitCriteria::CO2),
+            Err(FilterError::Emptied { column: 0 })
+        );
+        assert_eq!(
+            rating::<u64>("10\n10\n", &BitCriteria::OXYGEN),
+            Err(FilterError::Unresolved { remaining: 2 })
+        );
     }
 }
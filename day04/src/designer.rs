@@ -0,0 +1,227 @@
+use std::{collections::HashSet, error, fmt};
+
+use crate::{game::BingoGame, BingoBoard, Rules};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Goal {
+    Earliest,
+    Latest,
+    /// Win before any of the game's own boards.
+    BeatAll,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DesignError {
+    PoolTooSmall {
+        needed: usize,
+        available: usize,
+    },
+    /// Even the earliest possible board only wins on draw `best`, while an existing board
+    /// already wins on draw `existing`.
+    CannotBeat {
+        best: usize,
+        existing: usize,
+    },
+}
+
+impl fmt::Display for DesignError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::PoolTooSmall { needed, available } => write!(
+                f,
+                "a board needs {} distinct numbers but only {} are drawn",
+                needed, available
+            ),
+            Self::CannotBeat { best, existing } => write!(
+                f,
+                "the earliest possible board wins on draw {}, an existing board on draw {}",
+                best, existing
+            ),
+        }
+    }
+}
+
+impl error::Error for DesignError {}
+
+/// A board built for a goal, and the draw (counting from 1) it wins on.
+#[derive(Debug, PartialEq)]
+pub struct Design {
+    pub width: usize,
+    pub numbers: Vec<u32>,
+    pub wins_on: usize,
+}
+
+/// Writes the board the way `parse_input` reads it, right-aligned like the puzzle input.
+impl fmt::Display for Design {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.numbers.iter().max().map_or(1, |n| n.to_string().len());
+        for row in self.numbers.chunks(self.width) {
+            let row = row
+                .iter()
+                .map(|n| format!("{:>digits$}", n, digits = digits))
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Designs a `width` by `height` board from the game's drawn numbers, for the standard rules.
+/// The game's own boards are played by the standard rules too when beating them, whatever
+/// `game.rules` says, so both sides of the comparison play the same game.
+///
+/// Both extremes are optimal. A completed line holds at least `min(width, height)` numbers, so no
+/// board wins before that many distinct numbers are drawn, and putting the first of them in one
+/// line achieves it. In the other direction every row and every column needs a number drawn no
+/// earlier than the win, which takes `max(width, height)` distinct numbers; spreading the last
+/// ones drawn along a diagonal that touches every row and column achieves that bound.
+pub fn design(
+    game: &BingoGame,
+    width: usize,
+    height: usize,
+    goal: Goal,
+) -> Result<Design, DesignError> {
+    // Distinct numbers in the order they are first drawn.
+    let mut drawn = HashSet::new();
+    let pool = game
+        .numbers
+        .iter()
+        .copied()
+        .filter(|&n| drawn.insert(n))
+        .collect::<Vec<_>>();
+
+    let cells = width * height;
+    if pool.len() < cells || cells == 0 {
+        return Err(DesignError::PoolTooSmall {
+            needed: cells,
+            available: pool.len(),
+        });
+    }
+
+    let mut numbers = vec![0; cells];
+    match goal {
+        Goal::Earliest | Goal::BeatAll => {
+            let line = if width <= height {
+                (0..width).collect::<Vec<_>>()
+            } else {
+                (0..height).map(|y| y * width).collect()
+            };
+            let (first, rest) = pool.split_at(line.len());
+            fill(&mut numbers, &line, first, rest);
+        }
+        Goal::Latest => {
+            let n = width.max(height);
+            let line = (0..n)
+                .map(|i| (i % height) * width + i % width)
+                .collect::<Vec<_>>();
+            let (rest, last) = pool.split_at(pool.len() - n);
+            fill(&mut numbers, &line, last, rest);
+        }
+    }
+
+    let mut alone = game.clone();
+    alone.rules = Rules::STANDARD;
+    alone.boards = vec![BingoBoard::new(width, numbers.clone())];
+    let wins_on = alone.play().winner(0).unwrap().draw;
+
+    if goal == Goal::BeatAll {
+        let mut existing = game.clone();
+        existing.rules = Rules::STANDARD;
+        if let Some(win) = existing.play().winner(0) {
+            if win.draw <= wins_on {
+                return Err(DesignError::CannotBeat {
+                    best: wins_on,
+                    existing: win.draw,
+                });
+            }
+        }
+    }
+
+    Ok(Design {
+        width,
+        numbers,
+        wins_on,
+    })
+}
+
+/// Puts `chosen` in the `line` cells and fills every other cell from `rest`.
+fn fill(numbers: &mut [u32], line: &[usize], chosen: &[u32], rest: &[u32]) {
+    for (&i, &n) in line.iter().zip(chosen) {
+        numbers[i] = n;
+    }
+    let others = (0..numbers.len()).filter(|i| !line.contains(i));
+    for (i, &n) in others.zip(rest) {
+        numbers[i] = n;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{designer::*, parse_input, tests::SAMPLE};
+
+    /// Plays the design alongside the game's boards and returns the draw it wins on.
+    fn play(game: &BingoGame, design: &Design) -> Option<usize> {
+        let mut game = game.clone();
+        game.boards
+            .push(BingoBoard::new(design.width, design.numbers.clone()));
+        let board = game.boards.len() - 1;
+        let timeline = game.play();
+        let win = timeline.winners().find(|win| win.board == board);
+        win.map(|win| win.draw)
+    }
+
+    #[test]
+    fn test_design_extremes() {
+        let game = BingoGame::new(SAMPLE).unwrap();
+        for (width, height) in [(5, 5), (3, 4), (4, 2)] {
+            let earliest = design(&game, width, height, Goal::Earliest).unwrap();
+            assert_eq!(earliest.wins_on, width.min(height));
+            assert_eq!(play(&game, &earliest), Some(earliest.wins_on));
+
+            let latest = design(&game, width, height, Goal::Latest).unwrap();
+            assert_eq!(latest.wins_on, 27 + 1 - width.max(height));
+            assert_eq!(play(&game, &latest), Some(latest.wins_on));
+        }
+    }
+
+    #[test]
+    fn test_design_round_trip() {
+        let game = BingoGame::new(SAMPLE).unwrap();
+        let design = design(&game, 5, 5, Goal::BeatAll).unwrap();
+        let input = format!("7,4,9,5,11,17,23\n\n{}", design);
+        let (_, boards) = parse_input(&input).unwrap();
+        assert_eq!(boards[0].numbers, design.numbers);
+        assert_eq!(design.to_string().lines().next(), Some(" 7  4  9  5 11"));
+    }
+
+    #[test]
+    fn test_design_errors() {
+        let game = BingoGame::new(SAMPLE).unwrap();
+        assert_eq!(
+            design(&game, 6, 5, Goal::Latest),
+            Err(DesignError::PoolTooSmall {
+                needed: 30,
+                available: 27,
+            })
+        );
+
+        let game = BingoGame::new("1,2,3,4\n\n1 2\n3 4\n").unwrap();
+        assert_eq!(
+            design(&game, 2, 2, Goal::BeatAll),
+            Err(DesignError::CannotBeat {
+                best: 2,
+                existing: 2,
+            })
+        );
+
+        // The corners win on draw 4, but under the standard rules the top row only wins on 5.
+        let input = "1,3,7,9,2,4,5,6,8,10,11,12,13,14,15,16\n\n1 2 3\n4 5 6\n7 8 9\n";
+        let mut game = BingoGame::new(input).unwrap();
+        game.rules = Rules {
+            corners: true,
+            ..Rules::STANDARD
+        };
+        assert_eq!(game.clone().play().winner(0).unwrap().draw, 4);
+        assert_eq!(design(&game, 4, 4, Goal::BeatAll).unwrap().wins_on, 4);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{parse_input, Bingo, BingoBoard, ParseError, Rules};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Win {
+    pub board: usize,
+    /// How many numbers had been drawn, including the winning one.
+    pub draw: usize,
+    pub number: u32,
+    pub bingo: Bingo,
+    /// The sum of the unmarked numbers times the number that was just called.
+    pub score: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Drawn(u32),
+    Marked { board: usize, number: u32 },
+    Won(Win),
+}
+
+/// A small, fast generator (SplitMix64) so that shuffled games replay identically for a seed.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct BingoGame {
+    pub numbers: Vec<u32>,
+    pub boards: Vec<BingoBoard>,
+    pub rules: Rules,
+}
+
+impl BingoGame {
+    pub fn new(input: &str) -> Result<Self, ParseError> {
+        let (numbers, boards) = parse_input(input)?;
+        Ok(Self {
+            numbers,
+            boards,
+            rules: Rules::STANDARD,
+        })
+    }
+
+    pub fn boards(&self) -> usize {
+        self.boards.len()
+    }
+
+    /// Replaces the draw order with a random permutation of it.
+    pub fn shuffle(&mut self, seed: u64) {
+        Rng::new(seed).shuffle(&mut self.numbers);
+    }
+
+    /// Draws every number in turn. A board stops taking part once it has won.
+    pub fn play(mut self) -> Timeline {
+        let mut events = Vec::new();
+        let mut playing = vec![true; self.boards.len()];
+        for (draw, &number) in (1..).zip(&self.numbers) {
+            events.push(Event::Drawn(number));
+            for (board, b) in self.boards.iter_mut().enumerate() {
+                if !playing[board] || !b.try_mark(number, &self.rules) {
+                    continue;
+                }
+                events.push(Event::Marked { board, number });
+                if let Some(bingo) = b.bingo() {
+                    playing[board] = false;
+                    events.push(Event::Won(Win {
+                        board,
+                        draw,
+                        number,
+                        bingo,
+                        score: b.score() * number,
+                    }));
+                }
+            }
+        }
+
+        Timeline {
+            events,
+            boards: self.boards.len(),
+        }
+    }
+}
+
+/// Everything that happened in a game, in order.
+pub struct Timeline {
+    pub events: Vec<Event>,
+    boards: usize,
+}
+
+impl Timeline {
+    pub fn winners(&self) -> impl Iterator<Item = &Win> {
+        self.events.iter().filter_map(|event| match event {
+            Event::Won(win) => Some(win),
+            _ => None,
+        })
+    }
+
+    /// The `k`-th board to win, counting from 0.
+    pub fn winner(&self, k: usize) -> Option<&Win> {
+        self.winners().nth(k)
+    }
+
+    pub fn never_won(&self) -> Vec<usize> {
+        let mut won = vec![false; self.boards];
+        self.winners().for_each(|win| won[win.board] = true);
+        (0..self.boards).filter(|&board| !won[board]).collect()
+    }
+}
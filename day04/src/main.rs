@@ -1,99 +1,390 @@
-struct BingoBoard<'a> {
-    slots: [[&'a str; 5]; 5],
+mod designer;
+mod game;
+mod monte_carlo;
+mod validate;
+
+use std::{collections::HashMap, error, fmt};
+
+use game::{BingoGame, Event, Timeline};
+
+/// The pattern a board completed, with rows and columns indexed from the top left.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Bingo {
+    Row(usize),
+    Column(usize),
+    /// Top left to bottom right.
+    Diagonal,
+    /// Top right to bottom left.
+    AntiDiagonal,
+    Corners,
+    FullCard,
+}
+
+/// Which patterns win. Diagonals only count on square boards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rules {
+    lines: bool,
+    diagonals: bool,
+    corners: bool,
+    full_card: bool,
+}
+
+impl Rules {
+    const STANDARD: Self = Self {
+        lines: true,
+        diagonals: false,
+        corners: false,
+        full_card: false,
+    };
+}
+
+#[derive(Debug, PartialEq)]
+enum ParseError {
+    MissingDraws,
+    InvalidDraw(String),
+    InvalidNumber {
+        board: usize,
+        token: String,
+    },
+    /// Row `row` of `board` has `found` numbers where the first row had `expected`.
+    Ragged {
+        board: usize,
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingDraws => write!(f, "missing the line of drawn numbers"),
+            Self::InvalidDraw(s) => write!(f, "invalid drawn number: {:?}", s),
+            Self::InvalidNumber { board, token } => {
+                write!(f, "invalid number on board {}: {:?}", board, token)
+            }
+            Self::Ragged {
+                board,
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "board {} row {} has {} numbers, expected {}",
+                board, row, found, expected
+            ),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+#[derive(Debug, Clone)]
+struct BingoBoard {
+    width: usize,
+    height: usize,
+    /// Row-major, so the number at `(y, x)` is at index `y * width + x`.
+    numbers: Vec<u32>,
+    /// Bit `i % 64` of word `i / 64` is set once `numbers[i]` has been called.
+    marked: Vec<u64>,
+    /// Where each number sits on the board, so a call is marked without scanning.
+    cells: HashMap<u32, Vec<usize>>,
+    rows: Vec<usize>,
+    columns: Vec<usize>,
+    diagonals: [usize; 2],
+    /// The distinct corner cells; fewer than four on boards one cell wide or tall.
+    corner_cells: Vec<usize>,
+    corners: usize,
+    total: usize,
+    bingo: Option<Bingo>,
 }
 
-impl BingoBoard<'_> {
-    const MARK: &'static str = "X";
+impl BingoBoard {
+    fn new(width: usize, numbers: Vec<u32>) -> Self {
+        let height = numbers.len() / width;
+        let mut corner_cells = vec![0, width - 1, (height - 1) * width, height * width - 1];
+        corner_cells.sort_unstable();
+        corner_cells.dedup();
+
+        let mut cells = HashMap::<_, Vec<_>>::new();
+        for (i, &n) in numbers.iter().enumerate() {
+            cells.entry(n).or_default().push(i);
+        }
+
+        Self {
+            width,
+            height,
+            marked: vec![0; numbers.len().div_ceil(64)],
+            numbers,
+            cells,
+            rows: vec![0; height],
+            columns: vec![0; width],
+            diagonals: [0; 2],
+            corner_cells,
+            corners: 0,
+            total: 0,
+            bingo: None,
+        }
+    }
+
+    fn is_marked(&self, i: usize) -> bool {
+        self.marked[i / 64] & 1 << (i % 64) != 0
+    }
+
+    /// Marks every cell holding `number`, returning whether any of them was not marked yet.
+    fn try_mark(&mut self, number: u32, rules: &Rules) -> bool {
+        // Only other fields are written below, so the index is walked without copying it.
+        let cells = match self.cells.get(&number) {
+            Some(cells) => cells,
+            None => return false,
+        };
+
+        let square = self.width == self.height;
+        let mut newly_marked = false;
+        for &i in cells {
+            if self.is_marked(i) {
+                continue;
+            }
+            self.marked[i / 64] |= 1 << (i % 64);
+            newly_marked = true;
+
+            let (y, x) = (i / self.width, i % self.width);
+            self.rows[y] += 1;
+            self.columns[x] += 1;
+            if square && y == x {
+                self.diagonals[0] += 1;
+            }
+            if square && y + x == self.width - 1 {
+                self.diagonals[1] += 1;
+            }
+            if self.corner_cells.contains(&i) {
+                self.corners += 1;
+            }
+            self.total += 1;
+
+            if self.bingo.is_none() {
+                self.bingo = self.check(y, x, rules);
+            }
+        }
+        newly_marked
+    }
 
-    fn try_mark(&mut self, number: &str) {
-        self.slots
-            .iter_mut()
-            .flatten()
-            .filter(|n| n == &&number)
-            .for_each(|v| *v = BingoBoard::MARK);
+    /// Whether marking `(y, x)` completed a pattern allowed by `rules`.
+    fn check(&self, y: usize, x: usize, rules: &Rules) -> Option<Bingo> {
+        let square = self.width == self.height;
+        if rules.lines && self.rows[y] == self.width {
+            Some(Bingo::Row(y))
+        } else if rules.lines && self.columns[x] == self.height {
+            Some(Bingo::Column(x))
+        } else if rules.diagonals && square && self.diagonals[0] == self.width {
+            Some(Bingo::Diagonal)
+        } else if rules.diagonals && square && self.diagonals[1] == self.width {
+            Some(Bingo::AntiDiagonal)
+        } else if rules.corners && self.corners == self.corner_cells.len() {
+            Some(Bingo::Corners)
+        } else if rules.full_card && self.total == self.numbers.len() {
+            Some(Bingo::FullCard)
+        } else {
+            None
+        }
     }
 
-    fn has_bingo(&self) -> bool {
-        let horizontal = (0..5).any(|y| (0..5).all(|x| self.slots[y][x] == BingoBoard::MARK));
-        let vertical = (0..5).any(|x| (0..5).all(|y| self.slots[y][x] == BingoBoard::MARK));
-        horizontal || vertical
+    fn bingo(&self) -> Option<Bingo> {
+        self.bingo
     }
 
+    /// The sum of the numbers that have not been called.
     fn score(&self) -> u32 {
-        self.slots
+        self.numbers
             .iter()
-            .flatten()
-            .filter_map(|s| s.parse::<u32>().ok())
+            .enumerate()
+            .filter(|&(i, _)| !self.is_marked(i))
+            .map(|(_, n)| n)
             .sum()
     }
 }
 
-fn parse_input(input: &str) -> (Vec<&str>, Vec<BingoBoard>) {
-    let mut parts = input.split("\n\n");
-    let numbers: Vec<&str> = parts.next().unwrap().split(',').collect();
-
-    let boards = parts
+/// Reads the drawn numbers and the boards. Boards are separated by blank lines and may be any
+/// size, as long as every row of a board is as long as its first.
+fn parse_input(input: &str) -> Result<(Vec<u32>, Vec<BingoBoard>), ParseError> {
+    let mut lines = input.lines();
+    let numbers = lines
+        .next()
+        .filter(|s| !s.trim().is_empty())
+        .ok_or(ParseError::MissingDraws)?
+        .split(',')
         .map(|s| {
-            let b: [[&str; 5]; 5] = s
-                .lines()
-                .map(|s| s.split_whitespace().collect::<Vec<_>>().try_into().unwrap())
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap();
-            b
+            s.trim()
+                .parse()
+                .map_err(|_| ParseError::InvalidDraw(s.to_owned()))
         })
-        .map(|b| BingoBoard { slots: b })
-        .collect::<Vec<BingoBoard>>();
-
-    (numbers, boards)
-}
+        .collect::<Result<_, _>>()?;
 
-fn part1(input: &str) -> Option<u32> {
-    let (numbers, mut boards) = parse_input(input);
-    for number in numbers {
-        for board in boards.iter_mut() {
-            board.try_mark(number);
+    let mut boards = Vec::new();
+    let mut rows = Vec::new();
+    for line in lines.chain(std::iter::once("")) {
+        if !line.trim().is_empty() {
+            rows.push(line);
+            continue;
+        }
+        if rows.is_empty() {
+            continue;
+        }
 
-            if board.has_bingo() {
-                return Some(board.score() * number.parse::<u32>().unwrap());
+        let board = boards.len();
+        let mut width = None;
+        let mut numbers = Vec::new();
+        for (row, s) in rows.drain(..).enumerate() {
+            let before = numbers.len();
+            for token in s.split_whitespace() {
+                numbers.push(token.parse().map_err(|_| ParseError::InvalidNumber {
+                    board,
+                    token: token.to_owned(),
+                })?);
+            }
+            let found = numbers.len() - before;
+            let expected = *width.get_or_insert(found);
+            if found != expected {
+                return Err(ParseError::Ragged {
+                    board,
+                    row,
+                    expected,
+                    found,
+                });
             }
         }
+        boards.push(BingoBoard::new(width.unwrap(), numbers));
     }
 
-    None
+    Ok((numbers, boards))
 }
 
-fn part2(input: &str) -> Option<u32> {
-    let (numbers, mut boards) = parse_input(input);
+fn part1(timeline: &Timeline) -> Option<u32> {
+    timeline.winner(0).map(|win| win.score)
+}
+
+fn part2(timeline: &Timeline) -> Option<u32> {
+    timeline.winners().last().map(|win| win.score)
+}
+
+fn main() {
+    let input = include_str!("input.txt");
 
-    for number in numbers {
-        for board in boards.iter_mut() {
-            board.try_mark(number);
+    let mut rules = Rules::STANDARD;
+    let (mut strict, mut timeline, mut winner, mut seed, mut trials) =
+        (false, false, None, None, None);
+    let (mut goal, mut size) = (None, None);
+    let mut threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = Some(args.next().expect("missing seed").parse().unwrap()),
+            "--monte-carlo" => trials = Some(args.next().expect("missing trials").parse().unwrap()),
+            "--threads" => threads = args.next().expect("missing threads").parse().unwrap(),
+            "--strict" => strict = true,
+            "--timeline" => timeline = true,
+            "--diagonals" => rules.diagonals = true,
+            "--corners" => rules.corners = true,
+            "--full-card" => {
+                rules.lines = false;
+                rules.full_card = true;
+            }
+            "--design" => {
+                goal = match args.next().as_deref() {
+                    Some("earliest") => Some(designer::Goal::Earliest),
+                    Some("latest") => Some(designer::Goal::Latest),
+                    Some("beat") => Some(designer::Goal::BeatAll),
+                    _ => panic!("expected --design earliest|latest|beat"),
+                }
+            }
+            "--size" => {
+                let arg = args.next().expect("missing size");
+                let (w, h) = arg.split_once('x').expect("expected --size WIDTHxHEIGHT");
+                size = Some((w.parse().unwrap(), h.parse().unwrap()));
+            }
+            "--winner" => winner = Some(args.next().expect("missing k").parse().unwrap()),
+            _ => panic!("unknown argument: {:?}", arg),
         }
+    }
 
-        if boards.len() == 1 && boards[0].has_bingo() {
-            return Some(boards[0].score() * number.parse::<u32>().unwrap());
+    let mut game = BingoGame::new(input).unwrap();
+    game.rules = rules;
+    if strict {
+        if let Err(e) = game.validate_strict() {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    } else {
+        for anomaly in game.validate() {
+            eprintln!("warning: {}", anomaly);
         }
+    }
 
-        boards.retain(|b| !b.has_bingo());
+    if let Some(goal) = goal {
+        let (width, height) = size.unwrap_or_else(|| {
+            game.boards
+                .first()
+                .map_or((5, 5), |board| (board.width, board.height))
+        });
+        match designer::design(&game, width, height, goal) {
+            Ok(design) => {
+                eprintln!("Wins on draw {}", design.wins_on);
+                print!("{}", design);
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+        return;
     }
 
-    None
-}
+    if let Some(trials) = trials {
+        let odds = monte_carlo::simulate(&game, trials, seed.unwrap_or(0), threads);
+        println!("board  first   last    draws");
+        for (board, o) in odds.iter().enumerate() {
+            let draws = o
+                .expected_draws
+                .map_or("-".to_owned(), |d| format!("{:.2}", d));
+            println!("{:<6} {:.4}  {:.4}  {}", board, o.first, o.last, draws);
+        }
+        return;
+    }
 
-fn main() {
-    let input = include_str!("input.txt");
-    println!("Part 1 = {}", part1(input).unwrap());
-    println!("Part 2 = {}", part2(input).unwrap());
+    if let Some(seed) = seed {
+        game.shuffle(seed);
+    }
+    let played = game.play();
+    if timeline {
+        for event in &played.events {
+            match event {
+                Event::Drawn(number) => println!("draw {}", number),
+                Event::Marked { board, .. } => println!("    board {} marked", board),
+                Event::Won(win) => println!(
+                    "    board {} wins on {:?} with score {}",
+                    win.board, win.bingo, win.score
+                ),
+            }
+        }
+    }
+
+    if let Some(k) = winner {
+        match played.winner(k) {
+            Some(win) => println!("Winner {} = board {}, score {}", k, win.board, win.score),
+            None => println!("Winner {} = none", k),
+        }
+        return;
+    }
+
+    println!("Part 1 = {}", part1(&played).unwrap());
+    println!("Part 2 = {}", part2(&played).unwrap());
+    println!("Never won = {:?}", played.never_won());
 }
 
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
 
-    use crate::*;
+    use crate::{game::Win, *};
 
-    const SAMPLE: &str = indoc! {"
+    pub const SAMPLE: &str = indoc! {"
         7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
 
         22 13 17 11  0
@@ -117,11 +408,138 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(SAMPLE).unwrap(), 4512);
+        assert_eq!(part1(&BingoGame::new(SAMPLE).unwrap().play()), Some(4512));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(SAMPLE).unwrap(), 1924);
+        assert_eq!(part2(&BingoGame::new(SAMPLE).unwrap().play()), Some(1924));
+    }
+
+    #[test]
+    fn test_timeline() {
+        let played = BingoGame::new(SAMPLE).unwrap().play();
+        let winners = played.winners().collect::<Vec<_>>();
+        assert_eq!(winners.len(), 3);
+        assert_eq!(
+            winners[0],
+            &Win {
+                board: 2,
+                draw: 12,
+                number: 24,
+                bingo: Bingo::Row(0),
+                score: 4512,
+            }
+        );
+        assert_eq!(played.winner(1).map(|win| win.board), Some(0));
+        assert_eq!(played.never_won(), []);
+        assert_eq!(played.events[0], Event::Drawn(7));
+        assert_eq!(
+            played.events[1],
+            Event::Marked {
+                board: 0,
+                number: 7
+            }
+        );
+    }
+
+    #[test]
+    fn test_repeated_draw_marks_once() {
+        let played = BingoGame::new("1,1,2\n\n1 2\n3 4\n").unwrap().play();
+        let marked = played
+            .events
+            .iter()
+            .filter(|&event| {
+                *event
+                    == Event::Marked {
+                        board: 0,
+                        number: 1,
+                    }
+            })
+            .count();
+        assert_eq!(marked, 1);
+    }
+
+    #[test]
+    fn test_shuffle_is_seeded() {
+        let play = |seed| {
+            let mut game = BingoGame::new(SAMPLE).unwrap();
+            game.shuffle(seed);
+            game.play().events
+        };
+        assert_eq!(play(1), play(1));
+        assert_ne!(play(1), play(2));
+    }
+
+    #[test]
+    fn test_board_marking() {
+        let (_, mut boards) = parse_input(SAMPLE).unwrap();
+        let board = &mut boards[0];
+        assert!(!board.try_mark(99, &Rules::STANDARD));
+        for n in [22, 8, 21, 6] {
+            assert!(board.try_mark(n, &Rules::STANDARD));
+            assert_eq!(board.bingo(), None);
+        }
+        assert!(board.try_mark(1, &Rules::STANDARD));
+        assert!(!board.try_mark(1, &Rules::STANDARD));
+        assert_eq!(board.bingo(), Some(Bingo::Column(0)));
+        assert_eq!(board.numbers[0], 22);
+        assert_eq!(board.score(), 300 - 22 - 8 - 21 - 6 - 1);
+    }
+
+    #[test]
+    fn test_board_sizes() {
+        let (_, boards) = parse_input("1,2\n\n1 2 3\n4 5 6\n\n\n7\n8\n").unwrap();
+        assert_eq!((boards[0].width, boards[0].height), (3, 2));
+        assert_eq!((boards[1].width, boards[1].height), (1, 2));
+
+        assert_eq!(
+            parse_input("1,2\n\n1 2\n3 4\n\n1 2\n3\n").err(),
+            Some(ParseError::Ragged {
+                board: 1,
+                row: 1,
+                expected: 2,
+                found: 1,
+            })
+        );
+        assert_eq!(
+            parse_input("1,x\n\n1 2\n").err(),
+            Some(ParseError::InvalidDraw("x".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_rules() {
+        let input = indoc! {"
+            1,5,9,3,7,2,4,6,8
+
+            1 2 3
+            4 5 6
+            7 8 9
+        "};
+        let play = |rules| {
+            let mut game = BingoGame::new(input).unwrap();
+            game.rules = rules;
+            game.play().winner(0).map(|win| (win.number, win.bingo))
+        };
+
+        assert_eq!(play(Rules::STANDARD), Some((2, Bingo::Row(0))));
+        let diagonals = Rules {
+            diagonals: true,
+            ..Rules::STANDARD
+        };
+        assert_eq!(play(diagonals), Some((9, Bingo::Diagonal)));
+        let corners = Rules {
+            lines: false,
+            corners: true,
+            ..Rules::STANDARD
+        };
+        assert_eq!(play(corners), Some((7, Bingo::Corners)));
+        let full_card = Rules {
+            lines: false,
+            full_card: true,
+            ..Rules::STANDARD
+        };
+        assert_eq!(play(full_card), Some((8, Bingo::FullCard)));
     }
 }
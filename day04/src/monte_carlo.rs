@@ -0,0 +1,103 @@
+use std::thread;
+
+use crate::game::{BingoGame, Rng};
+
+/// How a board fares over many games with shuffled draws. Boards winning on the same draw are
+/// ordered by index, as in `BingoGame::play`.
+#[derive(Debug, PartialEq)]
+pub struct Odds {
+    pub first: f64,
+    pub last: f64,
+    /// The mean number of draws until the board wins, over the games it did win.
+    pub expected_draws: Option<f64>,
+}
+
+#[derive(Default, Clone)]
+struct Tally {
+    first: u64,
+    last: u64,
+    wins: u64,
+    draws: u64,
+}
+
+/// Plays `trials` games, each with its own shuffle of the draws, spread over `threads` threads.
+/// Every trial's seed is derived from `seed` up front and the tallies are plain counts, so the
+/// result only depends on `seed` and `trials`, never on the number of threads.
+pub fn simulate(game: &BingoGame, trials: usize, seed: u64, threads: usize) -> Vec<Odds> {
+    let mut rng = Rng::new(seed);
+    let seeds = (0..trials).map(|_| rng.next_u64()).collect::<Vec<_>>();
+    let chunk = trials.div_ceil(threads.max(1)).max(1);
+
+    let tallies = thread::scope(|scope| {
+        let handles = seeds
+            .chunks(chunk)
+            .map(|seeds| scope.spawn(move || tally(game, seeds)))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .fold(vec![Tally::default(); game.boards()], |mut acc, tallies| {
+                for (a, t) in acc.iter_mut().zip(tallies) {
+                    a.first += t.first;
+                    a.last += t.last;
+                    a.wins += t.wins;
+                    a.draws += t.draws;
+                }
+                acc
+            })
+    });
+
+    let trials = trials.max(1) as f64;
+    tallies
+        .into_iter()
+        .map(|t| Odds {
+            first: t.first as f64 / trials,
+            last: t.last as f64 / trials,
+            expected_draws: (t.wins > 0).then(|| t.draws as f64 / t.wins as f64),
+        })
+        .collect()
+}
+
+fn tally(game: &BingoGame, seeds: &[u64]) -> Vec<Tally> {
+    let mut tallies = vec![Tally::default(); game.boards()];
+    for &seed in seeds {
+        let mut game = game.clone();
+        game.shuffle(seed);
+        let timeline = game.play();
+
+        let mut winners = timeline.winners().peekable();
+        if let Some(win) = winners.peek() {
+            tallies[win.board].first += 1;
+        }
+        if let Some(win) = timeline.winners().last() {
+            tallies[win.board].last += 1;
+        }
+        for win in winners {
+            tallies[win.board].wins += 1;
+            tallies[win.board].draws += win.draw as u64;
+        }
+    }
+    tallies
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{monte_carlo::*, tests::SAMPLE};
+
+    #[test]
+    fn test_simulate_is_deterministic() {
+        let game = BingoGame::new(SAMPLE).unwrap();
+        let odds = simulate(&game, 200, 42, 1);
+        assert_eq!(simulate(&game, 200, 42, 3), odds);
+        assert_ne!(simulate(&game, 200, 43, 3), odds);
+
+        let first = odds.iter().map(|o| o.first).sum::<f64>();
+        let last = odds.iter().map(|o| o.last).sum::<f64>();
+        assert!((first - 1.0).abs() < 1e-9);
+        assert!((last - 1.0).abs() < 1e-9);
+        for o in &odds {
+            let draws = o.expected_draws.unwrap();
+            assert!((5.0..=27.0).contains(&draws));
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::{collections::HashMap, error, fmt};
+
+use crate::game::BingoGame;
+
+/// Something legal to parse but suspicious in a bingo file. Boards, rows, columns and draws are
+/// all indexed from 0.
+#[derive(Debug, PartialEq)]
+pub enum Anomaly {
+    DuplicateNumber {
+        board: usize,
+        number: u32,
+        cells: Vec<(usize, usize)>,
+    },
+    UnusedDraw {
+        draw: usize,
+        number: u32,
+    },
+    RepeatedDraw {
+        number: u32,
+        draws: Vec<usize>,
+    },
+    NeverWins {
+        board: usize,
+    },
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::DuplicateNumber {
+                board,
+                number,
+                cells,
+            } => {
+                let cells = cells
+                    .iter()
+                    .map(|(y, x)| format!("row {} column {}", y, x))
+                    .collect::<Vec<_>>();
+                write!(f, "board {} has {} at {}", board, number, cells.join(", "))
+            }
+            Self::UnusedDraw { draw, number } => {
+                write!(f, "draw {} ({}) is on no board", draw, number)
+            }
+            Self::RepeatedDraw { number, draws } => {
+                write!(
+                    f,
+                    "{} is drawn more than once, at draws {:?}",
+                    number, draws
+                )
+            }
+            Self::NeverWins { board } => write!(f, "board {} never wins", board),
+        }
+    }
+}
+
+/// Returned by `validate_strict` when there are any anomalies at all.
+#[derive(Debug, PartialEq)]
+pub struct ValidationError(pub Vec<Anomaly>);
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} anomalies found", self.0.len())?;
+        for anomaly in &self.0 {
+            write!(f, "\n  {}", anomaly)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for ValidationError {}
+
+impl BingoGame {
+    /// Lists every anomaly, board by board and then draw by draw. Whether a board ever wins
+    /// depends on the game's rules.
+    pub fn validate(&self) -> Vec<Anomaly> {
+        let mut anomalies = Vec::new();
+
+        for (board, b) in self.boards.iter().enumerate() {
+            let mut cells = HashMap::<_, Vec<_>>::new();
+            for (i, &number) in b.numbers.iter().enumerate() {
+                cells
+                    .entry(number)
+                    .or_default()
+                    .push((i / b.width, i % b.width));
+            }
+            let mut duplicates = cells
+                .into_iter()
+                .filter(|(_, cells)| cells.len() > 1)
+                .collect::<Vec<_>>();
+            duplicates.sort_unstable_by_key(|(_, cells)| cells[0]);
+            anomalies.extend(duplicates.into_iter().map(|(number, cells)| {
+                Anomaly::DuplicateNumber {
+                    board,
+                    number,
+                    cells,
+                }
+            }));
+        }
+
+        let mut draws = HashMap::<_, Vec<_>>::new();
+        for (draw, &number) in self.numbers.iter().enumerate() {
+            draws.entry(number).or_default().push(draw);
+            if !self.boards.iter().any(|b| b.cells.contains_key(&number)) {
+                anomalies.push(Anomaly::UnusedDraw { draw, number });
+            }
+        }
+        let mut repeated = draws
+            .into_iter()
+            .filter(|(_, draws)| draws.len() > 1)
+            .collect::<Vec<_>>();
+        repeated.sort_unstable_by_key(|(_, draws)| draws[0]);
+        anomalies.extend(
+            repeated
+                .into_iter()
+                .map(|(number, draws)| Anomaly::RepeatedDraw { number, draws }),
+        );
+
+        anomalies.extend(
+            self.clone()
+                .play()
+                .never_won()
+                .into_iter()
+                .map(|board| Anomaly::NeverWins { board }),
+        );
+        anomalies
+    }
+
+    /// Strict mode: fails if `validate` finds anything at all.
+    pub fn validate_strict(&self) -> Result<(), ValidationError> {
+        match self.validate() {
+            anomalies if anomalies.is_empty() => Ok(()),
+            anomalies => Err(ValidationError(anomalies)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{tests::SAMPLE, validate::*};
+
+    #[test]
+    fn test_validate() {
+        let input = indoc! {"
+            1,2,9,1,3
+
+            1 2
+            2 3
+
+            4 5
+            6 4
+        "};
+        let game = BingoGame::new(input).unwrap();
+        assert_eq!(
+            game.validate(),
+            [
+                Anomaly::DuplicateNumber {
+                    board: 0,
+                    number: 2,
+                    cells: vec![(0, 1), (1, 0)],
+                },
+                Anomaly::DuplicateNumber {
+                    board: 1,
+                    number: 4,
+                    cells: vec![(0, 0), (1, 1)],
+                },
+                Anomaly::UnusedDraw { draw: 2, number: 9 },
+                Anomaly::RepeatedDraw {
+                    number: 1,
+                    draws: vec![0, 3],
+                },
+                Anomaly::NeverWins { board: 1 },
+            ]
+        );
+        assert_eq!(game.validate_strict().unwrap_err().0.len(), 5);
+    }
+
+    #[test]
+    fn test_validate_sample() {
+        assert_eq!(BingoGame::new(SAMPLE).unwrap().validate_strict(), Ok(()));
+    }
+}
@@ -0,0 +1,208 @@
+use crate::{
+    raster::Raster,
+    storage::{Backend, FieldError, Storage},
+    Line,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+    Diagonal,
+    /// Any other slope, only allowed outside `Raster::Strict`.
+    Sloped,
+}
+
+impl Orientation {
+    pub const ALL: [Self; 4] = [
+        Self::Horizontal,
+        Self::Vertical,
+        Self::Diagonal,
+        Self::Sloped,
+    ];
+}
+
+impl Line {
+    /// A single point counts as horizontal.
+    pub fn orientation(&self) -> Orientation {
+        let (dx, dy) = self.delta();
+        let (dx, dy) = (dx.abs(), dy.abs());
+        if dy == 0 {
+            Orientation::Horizontal
+        } else if dx == 0 {
+            Orientation::Vertical
+        } else if dx == dy {
+            Orientation::Diagonal
+        } else {
+            Orientation::Sloped
+        }
+    }
+}
+
+/// What the lines of one orientation cover on their own.
+#[derive(Debug, PartialEq)]
+pub struct Breakdown {
+    pub lines: usize,
+    pub covered: usize,
+    /// Points reaching the threshold.
+    pub overlaps: usize,
+}
+
+/// The lines and how many of them cover each point.
+pub struct VentField {
+    lines: Vec<Line>,
+    raster: Raster,
+    backend: Backend,
+    counts: Box<dyn Storage>,
+}
+
+impl VentField {
+    /// Counts the lines with the backend `Backend::choose` picks for them.
+    pub fn new(lines: Vec<Line>, raster: Raster) -> Result<Self, FieldError> {
+        let backend = Backend::choose(&lines);
+        Self::with_backend(lines, raster, backend)
+    }
+
+    pub fn with_backend(
+        lines: Vec<Line>,
+        raster: Raster,
+        backend: Backend,
+    ) -> Result<Self, FieldError> {
+        let mut counts = backend.storage(&lines);
+        for line in &lines {
+            for point in line.points(raster)? {
+                counts.inc(point)?;
+            }
+        }
+        Ok(Self {
+            lines,
+            raster,
+            backend,
+            counts,
+        })
+    }
+
+    /// The number of lines covering `point`.
+    pub fn coverage(&self, point: (i32, i32)) -> u32 {
+        self.counts.get(point)
+    }
+
+    /// Every covered point and its coverage, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = ((i32, i32), u32)> + '_ {
+        self.counts.iter()
+    }
+
+    /// The highest coverage and the points reaching it, sorted by `(x, y)`.
+    pub fn max_overlap(&self) -> Option<(u32, Vec<(i32, i32)>)> {
+        let max = self.iter().map(|(_, count)| count).max()?;
+        let mut points = self.at_least(max);
+        points.sort_unstable();
+        Some((max, points))
+    }
+
+    /// The points covered by at least `threshold` lines, in no particular order.
+    pub fn at_least(&self, threshold: u32) -> Vec<(i32, i32)> {
+        self.iter()
+            .filter(|&(_, count)| count >= threshold)
+            .map(|(point, _)| point)
+            .collect()
+    }
+
+    /// The lines covering `point`, in input order.
+    pub fn lines_through(&self, point: (i32, i32)) -> Vec<&Line> {
+        self.lines
+            .iter()
+            .filter(|line| {
+                // Every line already rasterised once in `new`.
+                let mut points = line.points(self.raster).unwrap();
+                points.any(|p| p == point)
+            })
+            .collect()
+    }
+
+    /// Splits the field by orientation, counting each part as if the other lines were absent.
+    pub fn breakdown(&self, threshold: u32) -> Vec<(Orientation, Breakdown)> {
+        Orientation::ALL
+            .iter()
+            .map(|&orientation| {
+                let lines = self.lines.iter().copied();
+                let lines = lines.filter(|line| line.orientation() == orientation);
+                // A subset of the lines cannot overflow counters that held them all.
+                let part = Self::with_backend(lines.collect(), self.raster, self.backend).unwrap();
+                let breakdown = Breakdown {
+                    lines: part.lines.len(),
+                    covered: part.counts.len(),
+                    overlaps: part.at_least(threshold).len(),
+                };
+                (orientation, breakdown)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        field::*,
+        parse_input,
+        tests::{line, SAMPLE},
+    };
+
+    #[test]
+    fn test_orientation() {
+        let cases = [
+            (line(i32::MIN, 5, i32::MAX, 5), Orientation::Horizontal),
+            (line(0, i32::MAX, 0, i32::MIN), Orientation::Vertical),
+            (
+                line(i32::MAX, i32::MIN, i32::MIN, i32::MAX),
+                Orientation::Diagonal,
+            ),
+            (
+                line(-2_000_000_000, 0, 2_000_000_000, 1),
+                Orientation::Sloped,
+            ),
+        ];
+        for (line, orientation) in cases {
+            assert_eq!(line.orientation(), orientation);
+        }
+    }
+
+    #[test]
+    fn test_queries() {
+        for backend in [Backend::Sparse, Backend::Dense] {
+            let lines = parse_input(SAMPLE).unwrap();
+            check_queries(&VentField::with_backend(lines, Raster::Strict, backend).unwrap());
+        }
+    }
+
+    fn check_queries(field: &VentField) {
+        assert_eq!(field.coverage((4, 4)), 3);
+        assert_eq!(field.coverage((3, 0)), 0);
+        assert_eq!(field.max_overlap(), Some((3, vec![(4, 4), (6, 4)])));
+        assert_eq!(field.at_least(2).len(), 12);
+        assert_eq!(field.at_least(1).len(), 39);
+
+        let through = field.lines_through((6, 4));
+        let through = through
+            .iter()
+            .map(|line| (line.x1, line.y1))
+            .collect::<Vec<_>>();
+        assert_eq!(through, [(9, 4), (6, 4), (5, 5)]);
+
+        let breakdown = field.breakdown(2);
+        assert_eq!(
+            breakdown[0],
+            (
+                Orientation::Horizontal,
+                Breakdown {
+                    lines: 4,
+                    covered: 15,
+                    overlaps: 4,
+                }
+            )
+        );
+        assert_eq!(breakdown[1].1.lines, 2);
+        assert_eq!(breakdown[2].1.overlaps, 4);
+        assert_eq!(breakdown[3].1.lines, 0);
+    }
+}
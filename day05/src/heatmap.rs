@@ -0,0 +1,194 @@
+use std::{error, fmt};
+
+use crate::field::VentField;
+
+/// The most pixels a heatmap may have, the same cap as a dense field.
+const MAX_PIXELS: u64 = 1 << 28;
+
+/// The covered points span more than `MAX_PIXELS`.
+#[derive(Debug, PartialEq)]
+pub struct TooLarge {
+    pub width: u64,
+    pub height: u64,
+}
+
+impl fmt::Display for TooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a {}x{} heatmap is larger than {} pixels",
+            self.width, self.height, MAX_PIXELS
+        )
+    }
+}
+
+impl error::Error for TooLarge {}
+
+/// The smallest rectangle holding every covered point, inclusive.
+#[derive(Debug, PartialEq)]
+pub struct Bounds {
+    pub min: (i32, i32),
+    pub max: (i32, i32),
+}
+
+impl Bounds {
+    pub fn of(field: &VentField) -> Option<Self> {
+        let mut points = field.iter().map(|(point, _)| point);
+        let first = points.next()?;
+        let (min, max) = points.fold((first, first), |(min, max), (x, y)| {
+            ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+        });
+        Some(Self { min, max })
+    }
+
+    pub fn width(&self) -> u64 {
+        (i64::from(self.max.0) - i64::from(self.min.0)) as u64 + 1
+    }
+
+    pub fn height(&self) -> u64 {
+        (i64::from(self.max.1) - i64::from(self.min.1)) as u64 + 1
+    }
+
+    fn check_size(&self) -> Result<(), TooLarge> {
+        let (width, height) = (self.width(), self.height());
+        match width.checked_mul(height) {
+            Some(pixels) if pixels <= MAX_PIXELS => Ok(()),
+            _ => Err(TooLarge { width, height }),
+        }
+    }
+
+    /// The points row by row, top to bottom.
+    fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = (i32, i32)>> {
+        let (min_x, max_x) = (self.min.0, self.max.0);
+        (self.min.1..=self.max.1).map(move |y| (min_x..=max_x).map(move |x| (x, y)))
+    }
+}
+
+/// The map in the puzzle's notation: `.` for no line and the count otherwise, with `#` for
+/// counts too large for one digit.
+pub fn to_ascii(field: &VentField) -> Result<String, TooLarge> {
+    let bounds = Bounds::of(field);
+    if let Some(bounds) = &bounds {
+        bounds.check_size()?;
+    }
+    let mut map = String::new();
+    for row in bounds.iter().flat_map(Bounds::rows) {
+        for point in row {
+            map.push(match field.coverage(point) {
+                0 => '.',
+                n @ 1..=9 => char::from_digit(n, 10).unwrap(),
+                _ => '#',
+            });
+        }
+        map.push('\n');
+    }
+    Ok(map)
+}
+
+/// Renders one pixel per point, with the highest count at full intensity.
+fn render(
+    field: &VentField,
+    magic: &str,
+    pixel: impl Fn(f64) -> Vec<u8>,
+) -> Result<Vec<u8>, TooLarge> {
+    let bounds = Bounds::of(field).unwrap_or(Bounds {
+        min: (0, 0),
+        max: (0, 0),
+    });
+    bounds.check_size()?;
+    let max = field
+        .iter()
+        .map(|(_, count)| count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let header = format!("{}\n{} {}\n255\n", magic, bounds.width(), bounds.height());
+    let mut image = header.into_bytes();
+    for point in bounds.rows().flatten() {
+        image.extend(pixel(f64::from(field.coverage(point)) / f64::from(max)));
+    }
+    Ok(image)
+}
+
+/// A binary greyscale PGM image.
+pub fn to_pgm(field: &VentField) -> Result<Vec<u8>, TooLarge> {
+    render(field, "P5", |level| vec![(level * 255.0).round() as u8])
+}
+
+/// A binary PPM image, shading from black through red and yellow to white.
+pub fn to_ppm(field: &VentField) -> Result<Vec<u8>, TooLarge> {
+    render(field, "P6", |level| {
+        let channel = |from: f64| ((level * 3.0 - from).clamp(0.0, 1.0) * 255.0).round() as u8;
+        vec![channel(0.0), channel(1.0), channel(2.0)]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{heatmap::*, parse_input, raster::Raster, tests::SAMPLE};
+
+    fn sample() -> VentField {
+        VentField::new(parse_input(SAMPLE).unwrap(), Raster::Strict).unwrap()
+    }
+
+    #[test]
+    fn test_ascii() {
+        let expected = indoc! {"
+            1.1....11.
+            .111...2..
+            ..2.1.111.
+            ...1.2.2..
+            .112313211
+            ...1.2....
+            ..1...1...
+            .1.....1..
+            1.......1.
+            222111....
+        "};
+        assert_eq!(to_ascii(&sample()).unwrap(), expected);
+        let empty = VentField::new(Vec::new(), Raster::Strict).unwrap();
+        assert_eq!(to_ascii(&empty).unwrap(), "");
+    }
+
+    #[test]
+    fn test_images() {
+        let field = sample();
+        let pgm = to_pgm(&field).unwrap();
+        let header = b"P5\n10 10\n255\n";
+        assert_eq!(&pgm[..header.len()], header);
+        assert_eq!(pgm.len(), header.len() + 100);
+        // Row 4 holds the only 3s.
+        let row = &pgm[header.len() + 40..header.len() + 50];
+        assert_eq!(row, [0, 85, 85, 170, 255, 85, 255, 170, 85, 85]);
+
+        let ppm = to_ppm(&field).unwrap();
+        assert_eq!(ppm.len(), b"P6\n10 10\n255\n".len() + 300);
+        assert_eq!(ppm[ppm.len() - 3..], [0, 0, 0]);
+        assert_eq!(ppm[13..16], [255, 0, 0]);
+    }
+
+    #[test]
+    fn test_too_large() {
+        let input =
+            "-1000000000,0 -> -1000000000,0\n1000000000,1000000000 -> 1000000000,1000000000";
+        let field = VentField::new(parse_input(input).unwrap(), Raster::Strict).unwrap();
+        let error = TooLarge {
+            width: 2_000_000_001,
+            height: 1_000_000_001,
+        };
+        assert_eq!(to_ascii(&field), Err(error));
+        assert!(to_pgm(&field).is_err());
+        assert!(to_ppm(&field).is_err());
+
+        let input = "-2147483648,0 -> -2147483648,0\n2147483647,0 -> 2147483647,0";
+        let field = VentField::new(parse_input(input).unwrap(), Raster::Strict).unwrap();
+        let error = TooLarge {
+            width: 1 << 32,
+            height: 1,
+        };
+        assert_eq!(to_pgm(&field), Err(error));
+    }
+}
@@ -1,76 +1,343 @@
-use std::{cmp::Ordering, collections::HashMap};
+mod field;
+mod heatmap;
+mod pairwise;
+mod raster;
+mod storage;
 
-use sscanf::scanf;
+use std::{error, fmt, io::Write};
 
-type Vents = HashMap<(i32, i32), u8>;
+use field::VentField;
+use raster::{Raster, SlopeError};
+use storage::FieldError;
 
-trait Counter {
-    fn inc(&mut self, line: Line);
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Line {
+    x1: i32,
+    x2: i32,
+    y1: i32,
+    y2: i32,
+    /// The input line it was read from, counting from 1.
+    number: usize,
 }
 
-impl Counter for Vents {
-    fn inc(&mut self, Line { x1, x2, y1, y2 }: Line) {
-        let dx = match x1.cmp(&x2) {
-            Ordering::Less => 1,
-            Ordering::Greater => -1,
-            Ordering::Equal => 0,
-        };
-        let dy = match y1.cmp(&y2) {
-            Ordering::Less => 1,
-            Ordering::Greater => -1,
-            Ordering::Equal => 0,
-        };
-
-        let mut x = x1;
-        let mut y = y1;
+#[derive(Debug, PartialEq)]
+enum ParseError {
+    /// At a line and column, what was expected and what was found instead.
+    Expected(usize, usize, &'static str, String),
+    InvalidNumber(usize, usize, std::num::ParseIntError, String),
+}
 
-        *self.entry((x, y)).or_insert(0) += 1;
-        while x != x2 || y != y2 {
-            x += dx;
-            y += dy;
-            *self.entry((x, y)).or_insert(0) += 1;
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Expected(line, column, expected, found) => write!(
+                f,
+                "line {}, column {}: expected {}, found {:?}",
+                line, column, expected, found
+            ),
+            Self::InvalidNumber(line, column, e, s) => {
+                write!(f, "line {}, column {}: {}: {:?}", line, column, e, s)
+            }
         }
     }
 }
 
-struct Line {
-    x1: i32,
-    x2: i32,
-    y1: i32,
-    y2: i32,
+impl error::Error for ParseError {}
+
+/// Reads one input line from left to right, skipping whitespace between tokens.
+struct Cursor<'a> {
+    number: usize,
+    text: &'a str,
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    fn rest(&self) -> &str {
+        self.text[self.pos..].trim_start()
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.pos = self.text.len() - self.rest().len();
+    }
+
+    fn column(&self) -> usize {
+        self.text[..self.pos].chars().count() + 1
+    }
+
+    fn expected(&self, expected: &'static str) -> ParseError {
+        let found = self
+            .rest()
+            .split_whitespace()
+            .next()
+            .unwrap_or("end of line");
+        ParseError::Expected(self.number, self.column(), expected, found.to_string())
+    }
+
+    fn token(&mut self, token: &'static str) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if !self.rest().starts_with(token) {
+            return Err(self.expected(token));
+        }
+        self.pos += token.len();
+        Ok(())
+    }
+
+    fn coordinate(&mut self) -> Result<i32, ParseError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let sign = usize::from(rest.starts_with('-'));
+        let digits = rest[sign..].bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return Err(self.expected("a coordinate"));
+        }
+        let s = &rest[..sign + digits];
+        let n = s
+            .parse()
+            .map_err(|e| ParseError::InvalidNumber(self.number, self.column(), e, s.to_string()))?;
+        self.pos += s.len();
+        Ok(n)
+    }
+
+    fn end(&mut self) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if self.rest().is_empty() {
+            Ok(())
+        } else {
+            Err(self.expected("end of line"))
+        }
+    }
 }
 
-fn parse_input(input: &str) -> impl Iterator<Item = Line> + '_ {
+/// Reads `x1,y1 -> x2,y2` lines, allowing whitespace around every token and skipping blank lines.
+fn parse_input(input: &str) -> Result<Vec<Line>, ParseError> {
     input
         .lines()
-        .map(|s| scanf!(s, "{},{} -> {},{}", i32, i32, i32, i32).unwrap())
-        .map(|(x1, y1, x2, y2)| Line { x1, x2, y1, y2 })
+        .enumerate()
+        .filter(|(_, text)| !text.trim().is_empty())
+        .map(|(i, text)| {
+            let mut cursor = Cursor {
+                number: i + 1,
+                text,
+                pos: 0,
+            };
+            let x1 = cursor.coordinate()?;
+            cursor.token(",")?;
+            let y1 = cursor.coordinate()?;
+            cursor.token("->")?;
+            let x2 = cursor.coordinate()?;
+            cursor.token(",")?;
+            let y2 = cursor.coordinate()?;
+            cursor.end()?;
+            Ok(Line {
+                x1,
+                x2,
+                y1,
+                y2,
+                number: i + 1,
+            })
+        })
+        .collect()
 }
 
-fn part1(input: &str) -> i32 {
-    let lines = parse_input(input);
+/// How overlapping points are counted. Both give the same answers wherever `Pairwise` applies;
+/// `Grid` is fast for short lines, `Pairwise` for long lines over a large area.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Engine {
+    /// Counts every covered point in a `VentField`.
+    Grid,
+    /// Intersects every pair of lines.
+    Pairwise,
+}
 
-    let mut vents = Vents::default();
-    lines
-        .filter(|Line { x1, x2, y1, y2 }| x1 == x2 || y1 == y2) // Filter diagonal lines.
-        .for_each(|line| vents.inc(line));
+/// Why lines could not be counted. The pairwise engine reports what it cannot count instead of
+/// quietly handing the work to the grid, which would skew benchmarks.
+#[derive(Debug, PartialEq)]
+enum CountError {
+    Field(FieldError),
+    /// A pair of lines says nothing about points covered fewer than two times.
+    PairwiseThreshold(u32),
+    /// Bresenham cells of a sloped line are not lattice points, so pairs cannot be intersected.
+    PairwiseBresenham(Line),
+}
 
-    vents.values().filter(|&v| *v >= 2).count() as i32
+impl fmt::Display for CountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Field(e) => e.fmt(f),
+            Self::PairwiseThreshold(threshold) => write!(
+                f,
+                "the pairwise engine needs a threshold of at least 2, got {}",
+                threshold
+            ),
+            Self::PairwiseBresenham(line) => write!(
+                f,
+                "line {}: the pairwise engine cannot intersect sloped Bresenham lines",
+                line.number
+            ),
+        }
+    }
 }
 
-fn part2(input: &str) -> i32 {
-    let lines = parse_input(input);
+impl error::Error for CountError {}
 
-    let mut counter = Vents::default();
-    lines.for_each(|l| counter.inc(l));
+impl From<FieldError> for CountError {
+    fn from(e: FieldError) -> Self {
+        Self::Field(e)
+    }
+}
+
+impl Engine {
+    fn count_overlaps(
+        &self,
+        lines: &[Line],
+        raster: Raster,
+        threshold: u32,
+    ) -> Result<usize, CountError> {
+        match self {
+            Self::Pairwise => {
+                if threshold < 2 {
+                    return Err(CountError::PairwiseThreshold(threshold));
+                }
+                if let Some(&line) = lines.iter().find(|line| !line.is_straight()) {
+                    match raster {
+                        Raster::Strict => return Err(FieldError::from(SlopeError { line }).into()),
+                        Raster::Bresenham => return Err(CountError::PairwiseBresenham(line)),
+                        Raster::Lattice => {}
+                    }
+                }
+                Ok(pairwise::count_overlaps(lines, threshold as usize))
+            }
+            Self::Grid => Ok(VentField::new(lines.to_vec(), raster)?
+                .at_least(threshold)
+                .len()),
+        }
+    }
+}
 
-    counter.values().filter(|&v| *v >= 2).count() as i32
+fn part1(lines: &[Line], engine: Engine, threshold: u32) -> Result<i32, CountError> {
+    let lines = lines
+        .iter()
+        .copied()
+        .filter(|Line { x1, x2, y1, y2, .. }| x1 == x2 || y1 == y2) // Filter diagonal lines.
+        .collect::<Vec<_>>();
+    Ok(engine.count_overlaps(&lines, Raster::Strict, threshold)? as i32)
+}
+
+fn part2(
+    lines: &[Line],
+    engine: Engine,
+    raster: Raster,
+    threshold: u32,
+) -> Result<i32, CountError> {
+    Ok(engine.count_overlaps(lines, raster, threshold)? as i32)
 }
 
 fn main() {
     let input = include_str!("input.txt");
-    println!("Part 1 = {}", part1(input));
-    println!("Part 2 = {}", part2(input));
+
+    let (mut engine, mut raster) = (Engine::Grid, Raster::Strict);
+    let (mut ascii, mut pgm, mut ppm) = (false, None, None);
+    let (mut threshold, mut at, mut max, mut breakdown) = (2, None, false, false);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--engine" => {
+                engine = match args.next().as_deref() {
+                    Some("grid") => Engine::Grid,
+                    Some("pairwise") => Engine::Pairwise,
+                    _ => panic!("expected --engine grid|pairwise"),
+                }
+            }
+            "--raster" => {
+                raster = match args.next().as_deref() {
+                    Some("strict") => Raster::Strict,
+                    Some("bresenham") => Raster::Bresenham,
+                    Some("lattice") => Raster::Lattice,
+                    _ => panic!("expected --raster strict|bresenham|lattice"),
+                }
+            }
+            "--threshold" => threshold = args.next().expect("missing threshold").parse().unwrap(),
+            "--at" => {
+                let arg = args.next().expect("missing point");
+                let (x, y) = arg.split_once(',').expect("expected --at X,Y");
+                at = Some((x.parse().unwrap(), y.parse().unwrap()));
+            }
+            "--max" => max = true,
+            "--breakdown" => breakdown = true,
+            "--ascii" => ascii = true,
+            "--pgm" => pgm = Some(args.next().expect("missing PGM path")),
+            "--ppm" => ppm = Some(args.next().expect("missing PPM path")),
+            _ => panic!("unknown argument: {:?}", arg),
+        }
+    }
+
+    let lines = parse_input(input).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+
+    if ascii || pgm.is_some() || ppm.is_some() || at.is_some() || max || breakdown {
+        let field = VentField::new(lines, raster).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        });
+        if let Some(point) = at {
+            println!("{:?} is covered by {} lines:", point, field.coverage(point));
+            for line in field.lines_through(point) {
+                let Line {
+                    x1,
+                    x2,
+                    y1,
+                    y2,
+                    number,
+                } = line;
+                println!("  line {}: {},{} -> {},{}", number, x1, y1, x2, y2);
+            }
+        }
+        if max {
+            if let Some((count, points)) = field.max_overlap() {
+                println!("{} lines overlap at {:?}", count, points);
+            }
+        }
+        if breakdown {
+            for (orientation, part) in field.breakdown(threshold) {
+                println!(
+                    "{:?}: {} lines covering {} points, {} with at least {}",
+                    orientation, part.lines, part.covered, part.overlaps, threshold
+                );
+            }
+        }
+        let heatmap = |result: Result<Vec<u8>, heatmap::TooLarge>| {
+            result.unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            })
+        };
+        if ascii {
+            let map = heatmap(heatmap::to_ascii(&field).map(String::into_bytes));
+            std::io::stdout().write_all(&map).unwrap();
+        }
+        if let Some(path) = pgm {
+            std::fs::write(path, heatmap(heatmap::to_pgm(&field))).unwrap();
+        }
+        if let Some(path) = ppm {
+            std::fs::write(path, heatmap(heatmap::to_ppm(&field))).unwrap();
+        }
+        return;
+    }
+
+    let answers = part1(&lines, engine, threshold)
+        .and_then(|part1| Ok((part1, part2(&lines, engine, raster, threshold)?)));
+    match answers {
+        Ok((part1, part2)) => {
+            println!("Part 1 = {}", part1);
+            println!("Part 2 = {}", part2);
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -79,7 +346,7 @@ mod tests {
 
     use crate::*;
 
-    const SAMPLE: &str = indoc! {"
+    pub(crate) const SAMPLE: &str = indoc! {"
         0,9 -> 5,9
         8,0 -> 0,8
         9,4 -> 3,4
@@ -92,13 +359,99 @@ mod tests {
         5,5 -> 8,2
     "};
 
+    /// A line with no source line number, for tests that build lines by hand.
+    pub(crate) fn line(x1: i32, y1: i32, x2: i32, y2: i32) -> Line {
+        Line {
+            x1,
+            x2,
+            y1,
+            y2,
+            number: 0,
+        }
+    }
+
+    #[test]
+    fn test_parse_input() {
+        let lines = parse_input("  -3 ,4->  5,-6\n\n1,2 -> 3,4").unwrap();
+        let expected = Line {
+            x1: -3,
+            x2: 5,
+            y1: 4,
+            y2: -6,
+            number: 1,
+        };
+        assert_eq!(lines[0], expected);
+        assert_eq!(lines[1].number, 3);
+
+        let error = |input| parse_input(input).unwrap_err().to_string();
+        assert_eq!(
+            error("0,9 -> 5,9\n8,0 => 0,8"),
+            "line 2, column 5: expected ->, found \"=>\""
+        );
+        assert_eq!(
+            error("0,9 -> 5"),
+            "line 1, column 9: expected ,, found \"end of line\""
+        );
+        assert_eq!(
+            error("0,9 -> 5,9 7"),
+            "line 1, column 12: expected end of line, found \"7\""
+        );
+        assert_eq!(
+            error("0,x -> 5,9"),
+            "line 1, column 3: expected a coordinate, found \"x\""
+        );
+        assert_eq!(
+            error("0,9 -> 5,99999999999"),
+            "line 1, column 10: number too large to fit in target type: \"99999999999\""
+        );
+    }
+
     #[test]
     fn test_part1() {
-        assert_eq!(part1(SAMPLE), 5);
+        let lines = parse_input(SAMPLE).unwrap();
+        assert_eq!(part1(&lines, Engine::Grid, 2), Ok(5));
+        assert_eq!(part1(&lines, Engine::Pairwise, 2), Ok(5));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(SAMPLE), 12);
+        let lines = parse_input(SAMPLE).unwrap();
+        for raster in [Raster::Strict, Raster::Bresenham, Raster::Lattice] {
+            assert_eq!(part2(&lines, Engine::Grid, raster, 2), Ok(12));
+            assert_eq!(part2(&lines, Engine::Pairwise, raster, 2), Ok(12));
+        }
+
+        let input = parse_input(&format!("{}1,0 -> 4,9\n", SAMPLE)).unwrap();
+        let line = Line {
+            x1: 1,
+            x2: 4,
+            y1: 0,
+            y2: 9,
+            number: 11,
+        };
+        assert_eq!(
+            part2(&input, Engine::Grid, Raster::Strict, 2),
+            Err(CountError::Field(FieldError::Slope(SlopeError { line })))
+        );
+        assert_eq!(
+            part2(&input, Engine::Pairwise, Raster::Strict, 2),
+            Err(CountError::Field(FieldError::Slope(SlopeError { line })))
+        );
+        assert_eq!(part2(&input, Engine::Grid, Raster::Lattice, 2), Ok(13));
+        assert_eq!(part2(&input, Engine::Pairwise, Raster::Lattice, 2), Ok(13));
+        assert!(part2(&input, Engine::Grid, Raster::Bresenham, 2).is_ok());
+        assert_eq!(
+            part2(&input, Engine::Pairwise, Raster::Bresenham, 2),
+            Err(CountError::PairwiseBresenham(line))
+        );
+
+        for engine in [Engine::Grid, Engine::Pairwise] {
+            assert_eq!(part2(&lines, engine, Raster::Strict, 3), Ok(2));
+        }
+        assert_eq!(part2(&lines, Engine::Grid, Raster::Strict, 1), Ok(39));
+        assert_eq!(
+            part2(&lines, Engine::Pairwise, Raster::Strict, 1),
+            Err(CountError::PairwiseThreshold(1))
+        );
     }
 }
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use crate::Line;
+
+/// A line as the lattice points `(x, y) + t * (dx, dy)` for `t` in `0..=len`. The step is the
+/// smallest one pointing right, or down for vertical lines, so parallel segments always share it.
+struct Segment {
+    x: i64,
+    y: i64,
+    dx: i64,
+    dy: i64,
+    len: i64,
+}
+
+impl From<&Line> for Segment {
+    fn from(line: &Line) -> Self {
+        let (x, y) = (line.x1, line.y1).min((line.x2, line.y2));
+        let (x, y) = (i64::from(x), i64::from(y));
+        let ((dx, dy), len) = line.lattice_step();
+        Self { x, y, dx, dy, len }
+    }
+}
+
+impl Segment {
+    fn point(&self, t: i64) -> (i32, i32) {
+        ((self.x + t * self.dx) as i32, (self.y + t * self.dy) as i32)
+    }
+
+    /// Counts one more pair at each lattice point `self` and `other` have in common. Steps and
+    /// offsets reach 2^32, so their products are taken in `i128`.
+    fn intersect(&self, other: &Segment, pairs: &mut HashMap<(i32, i32), usize>) {
+        let (rx, ry) = (other.x - self.x, other.y - self.y);
+        let cross = |a: i64, b: i64, c: i64, d: i64| {
+            i128::from(a) * i128::from(b) - i128::from(c) * i128::from(d)
+        };
+        let det = cross(self.dy, other.dx, self.dx, other.dy);
+
+        if det == 0 {
+            // Parallel: only collinear segments meet, over a range of `t` along `self`.
+            if cross(rx, self.dy, ry, self.dx) != 0 {
+                return;
+            }
+            let start = if self.dx != 0 {
+                rx / self.dx
+            } else {
+                ry / self.dy
+            };
+            for t in start.max(0)..=(start + other.len).min(self.len) {
+                *pairs.entry(self.point(t)).or_insert(0) += 1;
+            }
+            return;
+        }
+
+        // Solve `self.point(t) == other.point(s)`. The lines may cross between lattice points, as
+        // two diagonals forming an X do, in which case `t` and `s` are not whole.
+        let t = cross(other.dx, ry, other.dy, rx);
+        let s = cross(self.dx, ry, self.dy, rx);
+        if t % det != 0 || s % det != 0 {
+            return;
+        }
+        let (t, s) = (t / det, s / det);
+        if (0..=i128::from(self.len)).contains(&t) && (0..=i128::from(other.len)).contains(&s) {
+            *pairs.entry(self.point(t as i64)).or_insert(0) += 1;
+        }
+    }
+}
+
+/// Counts the points covered by at least `threshold` lines, which must be 2 or more, by
+/// intersecting every pair of lines. Memory only grows with the number of overlapping points
+/// rather than the covered area. Lines cover their lattice points, as with `Raster::Lattice`.
+pub fn count_overlaps(lines: &[Line], threshold: usize) -> usize {
+    let segments = lines.iter().map(Segment::from).collect::<Vec<_>>();
+    let mut pairs = HashMap::new();
+    for (i, a) in segments.iter().enumerate() {
+        for b in &segments[i + 1..] {
+            a.intersect(b, &mut pairs);
+        }
+    }
+    // `n` lines through a point make `n * (n - 1) / 2` pairs there.
+    let min_pairs = threshold * (threshold - 1) / 2;
+    pairs.values().filter(|&&n| n >= min_pairs).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{pairwise::*, raster::Raster, tests::line, Engine};
+
+    #[test]
+    fn test_intersections() {
+        let count = |lines: &[Line]| count_overlaps(lines, 2);
+        assert_eq!(count(&[line(0, 0, 4, 0), line(6, 0, 2, 0)]), 3);
+        assert_eq!(count(&[line(0, 0, 4, 4), line(0, 4, 4, 0)]), 1);
+        assert_eq!(count(&[line(0, 0, 3, 3), line(0, 3, 3, 0)]), 0);
+        assert_eq!(count(&[line(2, 0, 2, 5), line(0, 3, 4, 3)]), 1);
+        assert_eq!(count(&[line(3, 3, 3, 3), line(0, 0, 5, 5)]), 1);
+        assert_eq!(count(&[line(0, 0, 2, 2), line(3, 3, 5, 5)]), 0);
+        assert_eq!(count(&[line(0, 0, 6, 3), line(2, 1, 8, 4)]), 3);
+        assert_eq!(count(&[line(0, 0, 6, 3), line(1, 0, 7, 3)]), 0);
+        assert_eq!(count(&[line(0, 0, 4, 6), line(0, 6, 4, 0)]), 1);
+        assert_eq!(count(&[line(0, 0, 3, 1), line(1, 0, 1, 5)]), 0);
+
+        // Steps this long overflow `i32` products, and at the edges of the range `i64` ones.
+        let (min, max) = (i32::MIN, i32::MAX);
+        assert_eq!(
+            count(&[line(0, 0, 100000, 99999), line(1, 0, 100001, 99999)]),
+            0
+        );
+        assert_eq!(
+            count(&[line(0, 0, 100000, 99999), line(100000, 0, 0, 99999)]),
+            0
+        );
+        assert_eq!(
+            count(&[line(min, max - 1, max, max), line(max - 1, min, max, max)]),
+            1
+        );
+        assert_eq!(
+            count(&[line(min, max - 1, max, max), line(max, min, max, max)]),
+            1
+        );
+        assert_eq!(
+            count(&[line(min, min, max, max), line(min, max, max, min)]),
+            0
+        );
+        assert_eq!(
+            count(&[
+                line(-2_000_000_000, 1, 2_000_000_000, -1),
+                line(-1, -2_000_000_000, 1, 2_000_000_000)
+            ]),
+            1
+        );
+
+        let star = [
+            line(0, 2, 4, 2),
+            line(2, 0, 2, 4),
+            line(0, 0, 4, 4),
+            line(1, 3, 3, 1),
+        ];
+        assert_eq!(count_overlaps(&star, 2), 1);
+        assert_eq!(count_overlaps(&star, 4), 1);
+        assert_eq!(count_overlaps(&star, 5), 0);
+    }
+
+    #[test]
+    fn test_engines_agree() {
+        // A small LCG keeps the generated lines the same on every run.
+        let mut state = 0x2545_f491_u64;
+        let mut next = |n: i32| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1);
+            (state >> 33) as i32 % n
+        };
+        let lines = (0..200)
+            .map(|_| {
+                let (x, y, len) = (next(40), next(40), next(20));
+                let (dx, dy) = [
+                    (1, 0),
+                    (0, 1),
+                    (1, 1),
+                    (1, -1),
+                    (-1, 0),
+                    (-1, -1),
+                    (2, 1),
+                    (-1, 3),
+                ][next(8) as usize];
+                line(x, y, x + dx * len, y + dy * len)
+            })
+            .collect::<Vec<_>>();
+
+        for threshold in 2..=4 {
+            let count = |engine: Engine, raster| engine.count_overlaps(&lines, raster, threshold);
+            assert_eq!(
+                count(Engine::Pairwise, Raster::Lattice),
+                count(Engine::Grid, Raster::Lattice)
+            );
+            assert!(count(Engine::Grid, Raster::Lattice).unwrap() > 0);
+        }
+
+        let straight = lines
+            .iter()
+            .copied()
+            .filter(Line::is_straight)
+            .collect::<Vec<_>>();
+        for raster in [Raster::Strict, Raster::Bresenham] {
+            assert_eq!(
+                Engine::Pairwise.count_overlaps(&straight, raster, 2),
+                Engine::Grid.count_overlaps(&straight, raster, 2)
+            );
+        }
+    }
+}
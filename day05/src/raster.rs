@@ -0,0 +1,226 @@
+use std::{error, fmt};
+
+use crate::Line;
+
+/// How a line is turned into the grid points it covers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Raster {
+    /// The puzzle's rules: only horizontal, vertical and 45° lines are allowed.
+    Strict,
+    /// Every cell a Bresenham line passes through, for any slope.
+    Bresenham,
+    /// Only the points lying exactly on the line, for any slope.
+    Lattice,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SlopeError {
+    pub line: Line,
+}
+
+impl fmt::Display for SlopeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Line {
+            x1,
+            x2,
+            y1,
+            y2,
+            number,
+        } = self.line;
+        write!(
+            f,
+            "line {}: {},{} -> {},{} is not horizontal, vertical or diagonal",
+            number, x1, y1, x2, y2
+        )
+    }
+}
+
+impl error::Error for SlopeError {}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl Line {
+    /// The change in x and y from the first endpoint to the second, in `i64` as it may not fit
+    /// in an `i32`.
+    pub fn delta(&self) -> (i64, i64) {
+        (
+            i64::from(self.x2) - i64::from(self.x1),
+            i64::from(self.y2) - i64::from(self.y1),
+        )
+    }
+
+    /// Whether the line is horizontal, vertical or at 45°, as the puzzle promises.
+    pub fn is_straight(&self) -> bool {
+        let (dx, dy) = self.delta();
+        let (dx, dy) = (dx.abs(), dy.abs());
+        dx == 0 || dy == 0 || dx == dy
+    }
+
+    /// The endpoints ordered so a line and its reverse are rasterised the same way.
+    fn ordered(&self) -> ((i32, i32), (i32, i32)) {
+        let (a, b) = ((self.x1, self.y1), (self.x2, self.y2));
+        if a <= b {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    }
+
+    /// The smallest step between points exactly on the line, and how many steps it spans.
+    pub fn lattice_step(&self) -> ((i64, i64), i64) {
+        let ((x1, y1), (x2, y2)) = self.ordered();
+        let (dx, dy) = (i64::from(x2) - i64::from(x1), i64::from(y2) - i64::from(y1));
+        let steps = gcd(dx.abs(), dy.abs());
+        if steps == 0 {
+            ((1, 0), 0)
+        } else {
+            ((dx / steps, dy / steps), steps)
+        }
+    }
+
+    /// The points the line covers. All three rasters agree on straight lines.
+    pub fn points(
+        &self,
+        raster: Raster,
+    ) -> Result<Box<dyn Iterator<Item = (i32, i32)>>, SlopeError> {
+        match raster {
+            Raster::Strict if !self.is_straight() => Err(SlopeError { line: *self }),
+            Raster::Strict | Raster::Lattice => {
+                let ((x, y), _) = self.ordered();
+                let (x, y) = (i64::from(x), i64::from(y));
+                let ((dx, dy), steps) = self.lattice_step();
+                // Every point lies between the endpoints, so it fits back in an `i32`.
+                Ok(Box::new(
+                    (0..=steps).map(move |t| ((x + t * dx) as i32, (y + t * dy) as i32)),
+                ))
+            }
+            Raster::Bresenham => Ok(Box::new(Bresenham::new(self))),
+        }
+    }
+}
+
+/// The integer-only Bresenham walk from one endpoint to the other, inclusive. It runs in `i64`,
+/// as the error term reaches twice the line's length.
+struct Bresenham {
+    x: i64,
+    y: i64,
+    sx: i64,
+    sy: i64,
+    dx: i64,
+    dy: i64,
+    err: i64,
+    remaining: i64,
+}
+
+impl Bresenham {
+    fn new(line: &Line) -> Self {
+        let ((x, y), (x2, y2)) = line.ordered();
+        let (x, y, x2, y2) = (i64::from(x), i64::from(y), i64::from(x2), i64::from(y2));
+        let (dx, dy) = ((x2 - x).abs(), -(y2 - y).abs());
+        Self {
+            x,
+            y,
+            sx: (x2 - x).signum(),
+            sy: (y2 - y).signum(),
+            dx,
+            dy,
+            err: dx + dy,
+            remaining: dx.max(-dy) + 1,
+        }
+    }
+}
+
+impl Iterator for Bresenham {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let point = (self.x as i32, self.y as i32);
+        let e2 = 2 * self.err;
+        if e2 >= self.dy {
+            self.err += self.dy;
+            self.x += self.sx;
+        }
+        if e2 <= self.dx {
+            self.err += self.dx;
+            self.y += self.sy;
+        }
+        Some(point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{raster::*, tests::line};
+
+    fn points(line: Line, raster: Raster) -> Vec<(i32, i32)> {
+        line.points(raster).unwrap().collect()
+    }
+
+    #[test]
+    fn test_rasters() {
+        for l in [
+            line(8, 0, 0, 8),
+            line(2, 2, 2, 1),
+            line(9, 4, 3, 4),
+            line(1, 1, 1, 1),
+        ] {
+            let strict = points(l, Raster::Strict);
+            assert_eq!(points(l, Raster::Bresenham), strict);
+            assert_eq!(points(l, Raster::Lattice), strict);
+        }
+
+        let steep = line(0, 0, 2, 6);
+        assert_eq!(
+            steep.points(Raster::Strict).err(),
+            Some(SlopeError { line: steep })
+        );
+        assert_eq!(points(steep, Raster::Lattice), [(0, 0), (1, 3), (2, 6)]);
+        assert_eq!(
+            points(steep, Raster::Bresenham),
+            [(0, 0), (0, 1), (1, 2), (1, 3), (1, 4), (2, 5), (2, 6)]
+        );
+        assert_eq!(
+            points(line(2, 6, 0, 0), Raster::Bresenham),
+            points(steep, Raster::Bresenham)
+        );
+        assert_eq!(points(line(0, 0, 3, 1), Raster::Lattice), [(0, 0), (3, 1)]);
+    }
+
+    #[test]
+    fn test_wide_lines() {
+        // Lines spanning most of the `i32` range, with few enough points to walk.
+        let wide = line(2_000_000_000, 2, -2_000_000_000, 0);
+        assert!(!wide.is_straight());
+        assert_eq!(wide.lattice_step(), ((2_000_000_000, 1), 2));
+        assert_eq!(
+            points(wide, Raster::Lattice),
+            [(-2_000_000_000, 0), (0, 1), (2_000_000_000, 2)]
+        );
+        let bresenham = wide.points(Raster::Bresenham).unwrap();
+        assert_eq!(
+            bresenham.take(3).collect::<Vec<_>>(),
+            [
+                (-2_000_000_000, 0),
+                (-1_999_999_999, 0),
+                (-1_999_999_998, 0)
+            ]
+        );
+
+        let full = line(i32::MAX, i32::MIN, i32::MIN, i32::MAX);
+        assert!(full.is_straight());
+        assert_eq!(full.lattice_step(), ((1, -1), (1 << 32) - 1));
+        let mut strict = full.points(Raster::Strict).unwrap();
+        assert_eq!(strict.next(), Some((i32::MIN, i32::MAX)));
+        assert_eq!(strict.next(), Some((i32::MIN + 1, i32::MAX - 1)));
+    }
+}
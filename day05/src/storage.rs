@@ -0,0 +1,250 @@
+use std::{collections::HashMap, error, fmt};
+
+use crate::{raster::SlopeError, Line};
+
+#[derive(Debug, PartialEq)]
+pub enum FieldError {
+    Slope(SlopeError),
+    /// More lines cross `point` than the backend's counters can hold.
+    Overflow {
+        point: (i32, i32),
+    },
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Slope(e) => e.fmt(f),
+            Self::Overflow { point: (x, y) } => {
+                write!(f, "too many lines cross {},{} to count", x, y)
+            }
+        }
+    }
+}
+
+impl error::Error for FieldError {}
+
+impl From<SlopeError> for FieldError {
+    fn from(e: SlopeError) -> Self {
+        Self::Slope(e)
+    }
+}
+
+/// Where a `VentField` keeps its counts.
+pub trait Storage {
+    /// Adds one line at `point`, failing rather than wrapping.
+    fn inc(&mut self, point: (i32, i32)) -> Result<(), FieldError>;
+
+    fn get(&self, point: (i32, i32)) -> u32;
+
+    /// Every covered point and its count, in no particular order.
+    fn iter(&self) -> Box<dyn Iterator<Item = ((i32, i32), u32)> + '_>;
+
+    /// The number of covered points.
+    fn len(&self) -> usize;
+}
+
+/// Counts in a hash map, for points scattered over a large area.
+#[derive(Default)]
+pub struct Sparse(HashMap<(i32, i32), u16>);
+
+impl Storage for Sparse {
+    fn inc(&mut self, point: (i32, i32)) -> Result<(), FieldError> {
+        let count = self.0.entry(point).or_insert(0);
+        *count = count.checked_add(1).ok_or(FieldError::Overflow { point })?;
+        Ok(())
+    }
+
+    fn get(&self, point: (i32, i32)) -> u32 {
+        self.0.get(&point).map_or(0, |&count| count.into())
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = ((i32, i32), u32)> + '_> {
+        Box::new(self.0.iter().map(|(&point, &count)| (point, count.into())))
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Counts in a row-major array over the lines' bounding box, for densely covered inputs.
+pub struct Dense {
+    min: (i32, i32),
+    width: usize,
+    height: usize,
+    counts: Vec<u32>,
+}
+
+impl Dense {
+    /// Every rasterised point lies within the box spanned by the lines' endpoints.
+    pub fn new(lines: &[Line]) -> Self {
+        let (min, max) = extent(lines).unwrap_or(((0, 0), (0, 0)));
+        let (width, height) = size(min, max);
+        let cells = width
+            .checked_mul(height)
+            .and_then(|cells| usize::try_from(cells).ok())
+            .expect("extent too large for a dense array");
+        Self {
+            min,
+            width: width as usize,
+            height: height as usize,
+            counts: vec![0; cells],
+        }
+    }
+
+    fn index(&self, (x, y): (i32, i32)) -> Option<usize> {
+        // Offsets are taken in `i64`, as points far outside the box overflow an `i32`.
+        let dx = usize::try_from(i64::from(x) - i64::from(self.min.0)).ok()?;
+        let dy = usize::try_from(i64::from(y) - i64::from(self.min.1)).ok()?;
+        (dx < self.width && dy < self.height).then(|| dy * self.width + dx)
+    }
+}
+
+impl Storage for Dense {
+    fn inc(&mut self, point: (i32, i32)) -> Result<(), FieldError> {
+        let i = self.index(point).expect("point outside the lines' extent");
+        self.counts[i] = self.counts[i]
+            .checked_add(1)
+            .ok_or(FieldError::Overflow { point })?;
+        Ok(())
+    }
+
+    fn get(&self, point: (i32, i32)) -> u32 {
+        self.index(point).map_or(0, |i| self.counts[i])
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = ((i32, i32), u32)> + '_> {
+        let points = self
+            .counts
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count > 0);
+        Box::new(points.map(move |(i, &count)| {
+            let (dx, dy) = (i % self.width, i / self.width);
+            ((self.min.0 + dx as i32, self.min.1 + dy as i32), count)
+        }))
+    }
+
+    fn len(&self) -> usize {
+        self.counts.iter().filter(|&&count| count > 0).count()
+    }
+}
+
+/// The width and height of the box from `min` to `max`, inclusive.
+fn size(min: (i32, i32), max: (i32, i32)) -> (u64, u64) {
+    let width = (i64::from(max.0) - i64::from(min.0)) as u64 + 1;
+    let height = (i64::from(max.1) - i64::from(min.1)) as u64 + 1;
+    (width, height)
+}
+
+/// The box spanned by the lines' endpoints, as its minimum and maximum corners.
+fn extent(lines: &[Line]) -> Option<((i32, i32), (i32, i32))> {
+    let mut points = lines
+        .iter()
+        .flat_map(|line| [(line.x1, line.y1), (line.x2, line.y2)]);
+    let first = points.next()?;
+    Some(points.fold((first, first), |(min, max), (x, y)| {
+        ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+    }))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backend {
+    Sparse,
+    Dense,
+}
+
+impl Backend {
+    /// The most cells a dense array may hold, 1 GiB of counters.
+    const DENSE_LIMIT: u64 = 1 << 28;
+
+    /// Picks `Dense` when the bounding box is small next to the number of points the lines
+    /// cover, since an array cell costs a fraction of a hash map entry, or when there are enough
+    /// lines to overflow `Sparse` counters and the box still fits in memory.
+    pub fn choose(lines: &[Line]) -> Self {
+        let area = extent(lines).map_or(0, |(min, max)| {
+            let (width, height) = size(min, max);
+            width.saturating_mul(height)
+        });
+        let points = lines
+            .iter()
+            .map(|line| {
+                let (dx, dy) = line.delta();
+                dx.unsigned_abs().max(dy.unsigned_abs()) + 1
+            })
+            .sum::<u64>();
+        let many_lines = lines.len() > usize::from(u16::MAX);
+        if area <= Self::DENSE_LIMIT && (area <= 4 * points || many_lines) {
+            Self::Dense
+        } else {
+            Self::Sparse
+        }
+    }
+
+    pub fn storage(&self, lines: &[Line]) -> Box<dyn Storage> {
+        match self {
+            Self::Sparse => Box::new(Sparse::default()),
+            Self::Dense => Box::new(Dense::new(lines)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{storage::*, tests::line};
+
+    #[test]
+    fn test_backends() {
+        let lines = [line(-2, 1, 3, 1), line(0, -1, 0, 4)];
+        for backend in [Backend::Sparse, Backend::Dense] {
+            let mut storage = backend.storage(&lines);
+            storage.inc((0, 1)).unwrap();
+            storage.inc((0, 1)).unwrap();
+            storage.inc((-2, 1)).unwrap();
+            assert_eq!(storage.get((0, 1)), 2);
+            assert_eq!(storage.get((-2, 1)), 1);
+            assert_eq!(storage.get((9, 9)), 0);
+            assert_eq!(storage.get((i32::MIN, 0)), 0);
+            assert_eq!(storage.get((i32::MAX, i32::MIN)), 0);
+            assert_eq!(storage.len(), 2);
+            let mut points = storage.iter().collect::<Vec<_>>();
+            points.sort_unstable();
+            assert_eq!(points, [((-2, 1), 1), ((0, 1), 2)]);
+        }
+
+        let mut sparse = Sparse::default();
+        for _ in 0..u16::MAX {
+            sparse.inc((5, 5)).unwrap();
+        }
+        assert_eq!(
+            sparse.inc((5, 5)),
+            Err(FieldError::Overflow { point: (5, 5) })
+        );
+    }
+
+    #[test]
+    fn test_choose() {
+        let dense = [line(0, 0, 9, 9), line(0, 9, 9, 0), line(0, 5, 9, 5)];
+        assert_eq!(Backend::choose(&dense), Backend::Dense);
+        let sparse = [line(0, 0, 9, 9), line(-5000, 7000, -4000, 8000)];
+        assert_eq!(Backend::choose(&sparse), Backend::Sparse);
+        let crowded = vec![line(0, 0, 0, 0); 70_000];
+        assert_eq!(Backend::choose(&crowded), Backend::Dense);
+        assert_eq!(Backend::choose(&[]), Backend::Dense);
+        let widest = [line(i32::MIN, i32::MIN, i32::MAX, i32::MAX)];
+        assert_eq!(Backend::choose(&widest), Backend::Sparse);
+
+        // The box spans every `i32` column, yet the offsets fit. The counts are left out, as
+        // only the indexing is under test.
+        assert_eq!(size((i32::MIN, 0), (i32::MAX, 0)), (1 << 32, 1));
+        let wide = Dense {
+            min: (i32::MIN, 0),
+            width: 1 << 32,
+            height: 1,
+            counts: Vec::new(),
+        };
+        assert_eq!(wide.index((i32::MAX, 0)), Some((1 << 32) - 1));
+        assert_eq!(wide.index((0, 1)), None);
+    }
+}
@@ -1,43 +1,405 @@
-fn parse_input(input: &str) -> impl Iterator<Item = usize> + '_ {
-    input.split(',').map(|s| s.trim().parse().unwrap())
+mod matrix;
+
+use std::{error, fmt};
+
+use num_bigint::BigUint;
+
+/// How a species' timers work: a fish spawns when its timer passes 0, restarting at
+/// `reset_timer`, and each of its `offspring` newborns starts at `newborn_timer`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LifecycleConfig {
+    reset_timer: usize,
+    newborn_timer: usize,
+    offspring: u64,
 }
 
-fn population(input: &str, days: usize) -> u64 {
-    let fishes = parse_input(input);
+impl LifecycleConfig {
+    const LANTERNFISH: Self = Self {
+        reset_timer: 6,
+        newborn_timer: 8,
+        offspring: 1,
+    };
 
-    // Set starting population.
-    let mut generations = [0; 9];
-    for fish in fishes {
-        generations[fish] += 1;
+    /// One bucket per timer value, up to the larger of the two starting timers.
+    fn buckets(&self) -> usize {
+        self.reset_timer.max(self.newborn_timer) + 1
+    }
+}
+
+/// A fish in the input that cannot be counted, at `position` in the list counting from 0.
+#[derive(Debug, PartialEq)]
+enum InputError {
+    NotATimer {
+        position: usize,
+        text: String,
+    },
+    /// The timer is above both starting timers, so it has no bucket.
+    TimerTooLarge {
+        position: usize,
+        timer: usize,
+        max: usize,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotATimer { position, text } => {
+                write!(f, "fish {} has no timer: {:?}", position, text)
+            }
+            Self::TimerTooLarge {
+                position,
+                timer,
+                max,
+            } => write!(
+                f,
+                "fish {} has timer {}, but timers only go up to {}",
+                position, timer, max
+            ),
+        }
+    }
+}
+
+impl error::Error for InputError {}
+
+fn parse_input(input: &str) -> impl Iterator<Item = Result<usize, InputError>> + '_ {
+    input.split(',').enumerate().map(|(position, s)| {
+        s.trim().parse().map_err(|_| InputError::NotATimer {
+            position,
+            text: s.trim().to_owned(),
+        })
+    })
+}
+
+/// The number of fish at each timer value.
+fn generations(input: &str, config: &LifecycleConfig) -> Result<Vec<u64>, InputError> {
+    let mut generations = vec![0; config.buckets()];
+    for (position, fish) in parse_input(input).enumerate() {
+        let timer = fish?;
+        let max = generations.len() - 1;
+        let bucket = generations
+            .get_mut(timer)
+            .ok_or(InputError::TimerTooLarge {
+                position,
+                timer,
+                max,
+            })?;
+        *bucket += 1;
+    }
+    Ok(generations)
+}
+
+/// A population count that reports overflow instead of wrapping.
+trait Count: Sized {
+    fn from_u64(n: u64) -> Self;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, n: u64) -> Option<Self>;
+}
+
+macro_rules! impl_count {
+    ($($t:ty),*) => {$(
+        impl Count for $t {
+            fn from_u64(n: u64) -> Self {
+                <$t>::from(n)
+            }
+
+            fn checked_add(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_add(*self, *other)
+            }
+
+            fn checked_mul(&self, n: u64) -> Option<Self> {
+                <$t>::checked_mul(*self, <$t>::from(n))
+            }
+        }
+    )*};
+}
+
+impl_count!(u64, u128);
+
+impl Count for BigUint {
+    fn from_u64(n: u64) -> Self {
+        n.into()
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_mul(&self, n: u64) -> Option<Self> {
+        Some(self * n)
+    }
+}
+
+/// The type populations are counted in. `Auto` starts with `U64` and moves to a wider type
+/// whenever the count overflows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Numeric {
+    Auto,
+    U64,
+    U128,
+    Big,
+}
+
+#[derive(Debug, PartialEq)]
+enum Population {
+    U64(u64),
+    U128(u128),
+    Big(BigUint),
+}
+
+impl fmt::Display for Population {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::U64(n) => n.fmt(f),
+            Self::U128(n) => n.fmt(f),
+            Self::Big(n) => n.fmt(f),
+        }
     }
+}
+
+/// The population no longer fits in `numeric` on `day`.
+#[derive(Debug, PartialEq)]
+struct OverflowError {
+    numeric: Numeric,
+    day: usize,
+}
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the population overflows {:?} on day {}",
+            self.numeric, self.day
+        )
+    }
+}
+
+impl error::Error for OverflowError {}
+
+/// Runs the population day by day, returning the first day its total overflows `C` on failure.
+fn simulate<C: Count>(start: &[u64], days: usize, config: &LifecycleConfig) -> Result<C, usize> {
+    let total = |generations: &[C], day| {
+        let total = generations
+            .iter()
+            .try_fold(C::from_u64(0), |total, n| total.checked_add(n));
+        total.ok_or(day)
+    };
 
-    for _ in 1..=days {
+    // Set starting population.
+    let mut generations = start.iter().map(|&n| C::from_u64(n)).collect::<Vec<_>>();
+    total(&generations, 0)?;
+
+    for day in 1..=days {
         generations.rotate_left(1);
-        generations[6] += generations[8];
+        let spawning = std::mem::replace(generations.last_mut().unwrap(), C::from_u64(0));
+        let reset = &generations[config.reset_timer];
+        generations[config.reset_timer] = reset.checked_add(&spawning).ok_or(day)?;
+        let newborns = spawning.checked_mul(config.offspring).ok_or(day)?;
+        let newborn = &generations[config.newborn_timer];
+        generations[config.newborn_timer] = newborn.checked_add(&newborns).ok_or(day)?;
+        // A bucket never exceeds the total, so this finds the first day that does not fit.
+        total(&generations, day)?;
     }
 
-    generations.iter().sum()
+    total(&generations, days)
+}
+
+/// The population after `days`, from the number of fish starting at each timer.
+fn population(
+    start: &[u64],
+    days: usize,
+    config: &LifecycleConfig,
+    numeric: Numeric,
+) -> Result<Population, OverflowError> {
+    let overflow = |numeric| move |day| OverflowError { numeric, day };
+    match numeric {
+        Numeric::Auto => population(start, days, config, Numeric::U64)
+            .or_else(|_| population(start, days, config, Numeric::U128))
+            .or_else(|_| population(start, days, config, Numeric::Big)),
+        Numeric::U64 => simulate(start, days, config)
+            .map(Population::U64)
+            .map_err(overflow(numeric)),
+        Numeric::U128 => simulate(start, days, config)
+            .map(Population::U128)
+            .map_err(overflow(numeric)),
+        Numeric::Big => simulate(start, days, config)
+            .map(Population::Big)
+            .map_err(overflow(numeric)),
+    }
 }
 
 fn main() {
     let input = include_str!("input.txt");
-    println!("Part 1 = {}", population(input, 80));
-    println!("Part 2 = {}", population(input, 256));
+
+    let mut config = LifecycleConfig::LANTERNFISH;
+    let (mut days, mut modulus, mut numeric) = (None, None, Numeric::Auto);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--reset" => config.reset_timer = args.next().expect("missing timer").parse().unwrap(),
+            "--newborn" => {
+                config.newborn_timer = args.next().expect("missing timer").parse().unwrap()
+            }
+            "--offspring" => {
+                config.offspring = args.next().expect("missing count").parse().unwrap()
+            }
+            "--days" => days = Some(args.next().expect("missing days").parse().unwrap()),
+            "--modulo" => modulus = Some(args.next().expect("missing modulus").parse().unwrap()),
+            "--numeric" => {
+                numeric = match args.next().as_deref() {
+                    Some("auto") => Numeric::Auto,
+                    Some("u64") => Numeric::U64,
+                    Some("u128") => Numeric::U128,
+                    Some("big") => Numeric::Big,
+                    _ => panic!("expected --numeric auto|u64|u128|big"),
+                }
+            }
+            _ => panic!("unknown argument: {:?}", arg),
+        }
+    }
+
+    if modulus == Some(0) {
+        eprintln!("the modulus must be above zero");
+        std::process::exit(1);
+    }
+
+    let generations = match generations(input, &config) {
+        Ok(generations) => generations,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(days) = days {
+        let count = matrix::project(&generations, days, &config, modulus);
+        println!("Day {} = {}", days, count);
+        return;
+    }
+
+    for (part, days) in [(1, 80), (2, 256)] {
+        match population(&generations, days, &config, numeric) {
+            Ok(count) => println!("Part {} = {}", part, count),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::*;
 
-    const SAMPLE: &str = "3,4,3,1,2\n";
+    pub(crate) const SAMPLE: &str = "3,4,3,1,2\n";
 
     #[test]
     fn test_part1() {
-        assert_eq!(population(SAMPLE, 80), 5934);
+        let config = LifecycleConfig::LANTERNFISH;
+        assert_eq!(
+            population(
+                &generations(SAMPLE, &config).unwrap(),
+                80,
+                &config,
+                Numeric::Auto
+            ),
+            Ok(Population::U64(5934))
+        );
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(population(SAMPLE, 256), 26984457539);
+        let config = LifecycleConfig::LANTERNFISH;
+        assert_eq!(
+            population(
+                &generations(SAMPLE, &config).unwrap(),
+                256,
+                &config,
+                Numeric::Auto
+            ),
+            Ok(Population::U64(26984457539))
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        let config = LifecycleConfig::LANTERNFISH;
+        let start = generations(SAMPLE, &config).unwrap();
+        let count = |days, numeric| population(&start, days, &config, numeric);
+
+        for (numeric, days) in [(Numeric::U64, 600), (Numeric::U128, 1200)] {
+            let day = match count(days, numeric) {
+                Err(OverflowError { numeric: n, day }) if n == numeric => day,
+                result => panic!("expected {:?} to overflow, got {:?}", numeric, result),
+            };
+            assert!(count(day - 1, numeric).is_ok());
+            assert!(count(day, numeric).is_err());
+        }
+
+        assert!(matches!(count(600, Numeric::Auto), Ok(Population::U128(_))));
+        assert!(matches!(count(1200, Numeric::Auto), Ok(Population::Big(_))));
+        for days in [400, 600, 1200] {
+            let exact = count(days, Numeric::Big).unwrap().to_string();
+            assert_eq!(count(days, Numeric::Auto).unwrap().to_string(), exact);
+        }
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let mut config = LifecycleConfig {
+            reset_timer: 1,
+            newborn_timer: 1,
+            offspring: 1,
+        };
+        assert_eq!(config.buckets(), 2);
+        let count = |config| {
+            population(
+                &generations("0", &config).unwrap(),
+                3,
+                &config,
+                Numeric::U64,
+            )
+        };
+        assert_eq!(count(config), Ok(Population::U64(4)));
+        config.offspring = 2;
+        assert_eq!(count(config), Ok(Population::U64(9)));
+
+        // Newborns that mature faster than their parents reset.
+        let config = LifecycleConfig {
+            reset_timer: 2,
+            newborn_timer: 0,
+            offspring: 1,
+        };
+        assert_eq!(config.buckets(), 3);
+        let start = generations("0", &config).unwrap();
+        let count = |days| population(&start, days, &config, Numeric::U64);
+        assert_eq!(count(1), Ok(Population::U64(2)));
+        assert_eq!(count(2), Ok(Population::U64(3)));
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let config = LifecycleConfig {
+            reset_timer: 2,
+            newborn_timer: 3,
+            offspring: 1,
+        };
+        assert_eq!(
+            generations(SAMPLE, &config),
+            Err(InputError::TimerTooLarge {
+                position: 1,
+                timer: 4,
+                max: 3,
+            })
+        );
+        assert_eq!(
+            generations("3,1,,2", &config),
+            Err(InputError::NotATimer {
+                position: 2,
+                text: String::new(),
+            })
+        );
+        assert_eq!(generations("3,1, 2\n", &config), Ok(vec![0, 1, 1, 1]));
     }
 }
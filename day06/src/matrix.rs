@@ -0,0 +1,144 @@
+use num_bigint::BigUint;
+
+use crate::LifecycleConfig;
+
+/// A square matrix of counts, row-major.
+#[derive(Debug, Clone, PartialEq)]
+struct Matrix {
+    size: usize,
+    cells: Vec<BigUint>,
+}
+
+impl Matrix {
+    fn identity(size: usize) -> Self {
+        let mut cells = vec![BigUint::default(); size * size];
+        for i in 0..size {
+            cells[i * size + i] = 1u32.into();
+        }
+        Self { size, cells }
+    }
+
+    /// Maps one day's timer buckets to the next: `next[i]` is the sum of `self[i][j] * today[j]`.
+    fn transition(config: &LifecycleConfig) -> Self {
+        let size = config.buckets();
+        let mut cells = vec![BigUint::default(); size * size];
+        for timer in 1..size {
+            cells[(timer - 1) * size + timer] = 1u32.into();
+        }
+        cells[config.reset_timer * size] += 1u32;
+        cells[config.newborn_timer * size] += config.offspring;
+        Self { size, cells }
+    }
+
+    fn mul(&self, other: &Self, modulus: Option<&BigUint>) -> Self {
+        let size = self.size;
+        let mut cells = vec![BigUint::default(); size * size];
+        for i in 0..size {
+            for k in 0..size {
+                let a = &self.cells[i * size + k];
+                if *a == BigUint::default() {
+                    continue;
+                }
+                for j in 0..size {
+                    cells[i * size + j] += a * &other.cells[k * size + j];
+                }
+            }
+        }
+        if let Some(m) = modulus {
+            cells.iter_mut().for_each(|cell| *cell %= m);
+        }
+        Self { size, cells }
+    }
+
+    /// Raises the matrix to the `n`-th power by repeated squaring, in `O(log n)` products.
+    fn pow(&self, mut n: u64, modulus: Option<&BigUint>) -> Self {
+        let (mut result, mut base) = (Self::identity(self.size), self.clone());
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result.mul(&base, modulus);
+            }
+            n >>= 1;
+            if n > 0 {
+                base = base.mul(&base, modulus);
+            }
+        }
+        result
+    }
+}
+
+/// The population after `days`, from the number of fish starting at each timer. Counts are
+/// exact, or reduced modulo `modulus` when given; the model asks for a prime, but any modulus
+/// above zero works. A zero modulus panics, so `main` rejects it first.
+pub fn project(
+    generations: &[u64],
+    days: u64,
+    config: &LifecycleConfig,
+    modulus: Option<u64>,
+) -> BigUint {
+    let modulus = modulus.map(BigUint::from);
+    let power = Matrix::transition(config).pow(days, modulus.as_ref());
+
+    let size = power.size;
+    let total = (0..size)
+        .flat_map(|i| (0..size).map(move |j| (i, j)))
+        .map(|(i, j)| &power.cells[i * size + j] * generations[j])
+        .sum::<BigUint>();
+    match modulus {
+        Some(m) => total % m,
+        None => total,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generations, matrix::*, population, tests::SAMPLE, Numeric, Population};
+
+    /// The iterative population, counted exactly.
+    fn exact(input: &str, days: usize, config: &LifecycleConfig) -> BigUint {
+        let start = generations(input, config).unwrap();
+        match population(&start, days, config, Numeric::Big) {
+            Ok(Population::Big(n)) => n,
+            result => panic!("expected an exact count, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_project() {
+        let config = LifecycleConfig::LANTERNFISH;
+        let start = generations(SAMPLE, &config).unwrap();
+        for days in (0..=256).step_by(17).chain([80, 256]) {
+            let iterative = exact(SAMPLE, days, &config);
+            let days = days as u64;
+            assert_eq!(project(&start, days, &config, None), iterative);
+            let modulo = project(&start, days, &config, Some(1_000_000_007));
+            assert_eq!(modulo, iterative % 1_000_000_007u32);
+        }
+
+        let config = LifecycleConfig {
+            reset_timer: 2,
+            newborn_timer: 0,
+            offspring: 3,
+        };
+        let start = generations("0,1,2,2", &config).unwrap();
+        for days in 0..20 {
+            let projected = project(&start, days as u64, &config, None);
+            assert_eq!(projected, exact("0,1,2,2", days, &config));
+        }
+    }
+
+    #[test]
+    fn test_project_far() {
+        // Too far for exact counts, but a modular version of the daily loop still runs.
+        let config = LifecycleConfig::LANTERNFISH;
+        let (days, m) = (1_000_000, 998_244_353);
+        let mut buckets = generations(SAMPLE, &config).unwrap();
+        for _ in 0..days {
+            buckets.rotate_left(1);
+            buckets[6] = (buckets[6] + buckets[8]) % m;
+        }
+        let expected = buckets.iter().sum::<u64>() % m;
+
+        let start = generations(SAMPLE, &config).unwrap();
+        assert_eq!(project(&start, days, &config, Some(m)), expected.into());
+    }
+}